@@ -0,0 +1,118 @@
+// Everything the user can ask memwatch to do from the keyboard, the command
+// palette, the global hotkey or IPC. All of them go through
+// `MemwatchApp::run_action`, so an action behaves the same wherever it is
+// triggered from.
+
+use crate::keybindings::KeyAction;
+use crate::settings::{ChartLine, Settings, SortMode};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    ToggleWindow,
+    CommandPalette,
+    FocusFilter,
+    SelectPrevious,
+    SelectNext,
+    PageUp,
+    PageDown,
+    SelectFirst,
+    SelectLast,
+    Sort(SortMode),
+    Terminate,
+    ForceKill,
+    SignalMenu,
+    ToggleTree,
+    OpenDetails,
+    CloseDialog,
+    ToggleChartSeries(ChartLine),
+    OpenSettings,
+    OpenHistory,
+    OpenExited,
+    SwitchProfile(String),
+    PinSnapshot,
+    ExportSnapshot,
+}
+
+impl From<KeyAction> for Action {
+    fn from(action: KeyAction) -> Self {
+        match action {
+            KeyAction::ToggleWindow => Action::ToggleWindow,
+            KeyAction::CommandPalette => Action::CommandPalette,
+            KeyAction::FocusFilter => Action::FocusFilter,
+            KeyAction::SelectPrevious => Action::SelectPrevious,
+            KeyAction::SelectNext => Action::SelectNext,
+            KeyAction::PageUp => Action::PageUp,
+            KeyAction::PageDown => Action::PageDown,
+            KeyAction::SelectFirst => Action::SelectFirst,
+            KeyAction::SelectLast => Action::SelectLast,
+            KeyAction::SortMemory => Action::Sort(SortMode::Memory),
+            KeyAction::SortCpu => Action::Sort(SortMode::Cpu),
+            KeyAction::SortDiskIo => Action::Sort(SortMode::DiskIo),
+            KeyAction::SortOomScore => Action::Sort(SortMode::OomScore),
+            KeyAction::Terminate => Action::Terminate,
+            KeyAction::ForceKill => Action::ForceKill,
+            KeyAction::SignalMenu => Action::SignalMenu,
+            KeyAction::ToggleTree => Action::ToggleTree,
+            KeyAction::OpenDetails => Action::OpenDetails,
+            KeyAction::CloseDialog => Action::CloseDialog,
+        }
+    }
+}
+
+impl Action {
+    /// The bindable action this corresponds to, if any.
+    pub fn key_action(&self) -> Option<KeyAction> {
+        KeyAction::ALL
+            .into_iter()
+            .find(|action| Action::from(*action) == *self)
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Action::ToggleChartSeries(line) => format!("Show / hide {} in chart", line.label()),
+            Action::OpenSettings => "Open settings".to_string(),
+            Action::OpenHistory => "Open signal history".to_string(),
+            Action::OpenExited => "Open recently exited".to_string(),
+            Action::SwitchProfile(name) => format!("Switch to profile \"{}\"", name),
+            Action::PinSnapshot => "Pin snapshot".to_string(),
+            Action::ExportSnapshot => "Export snapshot as JSON".to_string(),
+            _ => match self.key_action() {
+                Some(action) => action.label().to_string(),
+                None => format!("{:?}", self),
+            },
+        }
+    }
+}
+
+/// Actions offered by the command palette, in the order shown before
+/// anything is typed. Cursor movement and dialog closing are left out as
+/// they only make sense as keys.
+pub fn palette_actions(settings: &Settings) -> Vec<Action> {
+    let mut actions = vec![
+        Action::Sort(SortMode::Memory),
+        Action::Sort(SortMode::Cpu),
+        Action::Sort(SortMode::DiskIo),
+    ];
+    if cfg!(target_os = "linux") {
+        actions.push(Action::Sort(SortMode::OomScore));
+    }
+    actions.extend([
+        Action::ToggleTree,
+        Action::FocusFilter,
+        Action::OpenDetails,
+        Action::Terminate,
+        Action::ForceKill,
+        Action::SignalMenu,
+    ]);
+    actions.extend(ChartLine::ALL.into_iter().map(Action::ToggleChartSeries));
+    actions.extend([
+        Action::PinSnapshot,
+        Action::ExportSnapshot,
+        Action::OpenSettings,
+        Action::OpenHistory,
+        Action::OpenExited,
+    ]);
+    actions.extend(settings.profiles.keys().cloned().map(Action::SwitchProfile));
+    actions.push(Action::ToggleWindow);
+    actions
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum KillOrigin {
+    Gui,
+    Rule,
+}
+
+impl KillOrigin {
+    pub fn label(self) -> &'static str {
+        match self {
+            KillOrigin::Gui => "GUI",
+            KillOrigin::Rule => "Rule",
+        }
+    }
+}
+
+/// One line of the audit log. Stored as JSON lines so appends never need to
+/// rewrite the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub pid: u32,
+    pub name: String,
+    pub command_line: String,
+    pub user: String,
+    pub signal: String,
+    pub status: String,
+    pub origin: KillOrigin,
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn record(entry: &AuditEntry) -> Result<(), Box<dyn std::error::Error>> {
+    match log_path() {
+        Some(path) => record_to(&path, entry),
+        None => Ok(()),
+    }
+}
+
+fn record_to(path: &Path, entry: &AuditEntry) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Loads every entry, oldest first. Lines that fail to parse are skipped so a
+/// truncated write never hides the rest of the history.
+pub fn load() -> Vec<AuditEntry> {
+    log_path().map(|path| load_from(&path)).unwrap_or_default()
+}
+
+fn load_from(path: &Path) -> Vec<AuditEntry> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[cfg(not(test))]
+pub fn log_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("com", "memwatch", "memwatch")
+        .map(|dirs| dirs.config_dir().join("audit.log"))
+}
+
+/// Tests that signal or kill processes log to a per-run file instead of the
+/// user's real history.
+#[cfg(test)]
+pub fn log_path() -> Option<PathBuf> {
+    Some(std::env::temp_dir().join(format!("memwatch-audit-{}.log", std::process::id())))
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD HH:MM:SS UTC` without pulling in a
+/// date library.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let secs = timestamp % 86_400;
+
+    // Civil-from-days conversion (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        (secs % 3_600) / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_fixed_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_timestamp(951_868_799), "2000-02-29 23:59:59 UTC");
+        assert_eq!(format_timestamp(2_147_483_647), "2038-01-19 03:14:07 UTC");
+    }
+
+    #[test]
+    fn record_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("memwatch-roundtrip-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let entry = |pid, origin| AuditEntry {
+            timestamp: 1_700_000_000,
+            pid,
+            name: "worker".to_string(),
+            command_line: "worker --flag \"quoted\"".to_string(),
+            user: "alice".to_string(),
+            signal: "SIGTERM".to_string(),
+            status: "Success".to_string(),
+            origin,
+        };
+        record_to(&path, &entry(10, KillOrigin::Gui)).unwrap();
+        // A torn line from an interrupted write is skipped, not fatal.
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"timestamp\":1\n").unwrap();
+        record_to(&path, &entry(11, KillOrigin::Rule)).unwrap();
+
+        let loaded = load_from(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!((loaded[0].pid, loaded[0].origin), (10, KillOrigin::Gui));
+        assert_eq!((loaded[1].pid, loaded[1].origin), (11, KillOrigin::Rule));
+        assert_eq!(loaded[0].command_line, "worker --flag \"quoted\"");
+    }
+}
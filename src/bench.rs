@@ -0,0 +1,151 @@
+// `memwatch bench`: times each process source against the live system,
+// optionally after spawning idle children so the numbers reflect a busy build
+// server rather than a laptop. The children share one process group that is
+// killed when the run ends, panics or is interrupted.
+
+use crate::settings::ProcessSourceKind;
+use crate::source;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Process group of the spawned children, for the signal handler.
+static CHILD_GROUP: AtomicI32 = AtomicI32::new(0);
+
+/// Idle children in one process group, killed together on drop.
+struct Children {
+    group: i32,
+    list: Vec<Child>,
+}
+
+impl Children {
+    fn spawn(count: usize) -> Self {
+        let mut children = Children {
+            group: 0,
+            list: Vec::with_capacity(count),
+        };
+        for _ in 0..count {
+            // The first child starts the group and the rest join it.
+            match Command::new("sleep")
+                .arg("600")
+                .process_group(children.group)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+            {
+                Ok(child) => {
+                    if children.group == 0 {
+                        children.group = child.id() as i32;
+                        CHILD_GROUP.store(children.group, Ordering::SeqCst);
+                    }
+                    children.list.push(child);
+                }
+                Err(e) => {
+                    eprintln!("Stopped spawning after {} children: {}", children.list.len(), e);
+                    break;
+                }
+            }
+        }
+        children
+    }
+}
+
+impl Drop for Children {
+    fn drop(&mut self) {
+        if self.group != 0 {
+            CHILD_GROUP.store(0, Ordering::SeqCst);
+            unsafe { libc::killpg(self.group, libc::SIGKILL) };
+        }
+        for child in &mut self.list {
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Kills the children on Ctrl-C or SIGTERM, which skip `Drop`.
+extern "C" fn on_interrupt(signal: libc::c_int) {
+    let group = CHILD_GROUP.load(Ordering::SeqCst);
+    unsafe {
+        if group != 0 {
+            libc::killpg(group, libc::SIGKILL);
+        }
+        libc::_exit(128 + signal);
+    }
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let option = |name: &str, default: usize| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    };
+    let spawn = option("--spawn", 200);
+    let rounds = option("--rounds", 20).max(1);
+
+    unsafe {
+        libc::signal(libc::SIGINT, on_interrupt as *const () as libc::sighandler_t);
+        libc::signal(libc::SIGTERM, on_interrupt as *const () as libc::sighandler_t);
+    }
+    let children = Children::spawn(spawn);
+
+    println!("{} extra processes spawned, {} rounds per source", children.list.len(), rounds);
+    println!(
+        "{:<14}  {:>8}  {:>9}  {:>9}  {:>9}  {:>9}  {:>10}  {:>8}",
+        "SOURCE", "INIT ms", "MEAN ms", "MIN ms", "MAX ms", "CPU ms", "PROCESSES", "FILES"
+    );
+
+    let mut kinds = vec![ProcessSourceKind::Sysinfo];
+    if cfg!(target_os = "linux") {
+        kinds.push(ProcessSourceKind::Procfs);
+    }
+    for kind in kinds {
+        let init_start = Instant::now();
+        let mut source = source::create(kind);
+        let init = init_start.elapsed();
+
+        let cpu_before = cpu_time();
+        let mut samples = Vec::with_capacity(rounds);
+        for _ in 0..rounds {
+            thread::sleep(Duration::from_millis(50));
+            let start = Instant::now();
+            source.refresh();
+            samples.push(start.elapsed());
+        }
+        let cpu = cpu_time().saturating_sub(cpu_before);
+
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let total: Duration = samples.iter().sum();
+        let cost = source.cost();
+        println!(
+            "{:<14}  {:>8.1}  {:>9.2}  {:>9.2}  {:>9.2}  {:>9.1}  {:>10}  {:>8}",
+            kind.label(),
+            ms(init),
+            ms(total) / rounds as f64,
+            samples.iter().copied().map(ms).fold(f64::INFINITY, f64::min),
+            samples.iter().copied().map(ms).fold(0.0, f64::max),
+            ms(cpu) / rounds as f64,
+            source.processes().len(),
+            cost.files_read.map(|files| files.to_string()).unwrap_or_else(|| "-".to_string()),
+        );
+    }
+
+    drop(children);
+    0
+}
+
+/// User plus system CPU time consumed by this process so far.
+fn cpu_time() -> Duration {
+    unsafe {
+        let mut usage: libc::rusage = std::mem::zeroed();
+        if libc::getrusage(libc::RUSAGE_SELF, &mut usage) != 0 {
+            return Duration::ZERO;
+        }
+        let micros = |tv: libc::timeval| tv.tv_sec as u64 * 1_000_000 + tv.tv_usec as u64;
+        Duration::from_micros(micros(usage.ru_utime) + micros(usage.ru_stime))
+    }
+}
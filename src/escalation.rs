@@ -0,0 +1,118 @@
+use libc::{SIGHUP, SIGINT, SIGQUIT, SIGTERM};
+use serde::{Deserialize, Serialize};
+
+/// Signals a policy may send before escalating to SIGKILL.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GracefulSignal {
+    Term,
+    Int,
+    Hup,
+    Quit,
+}
+
+impl GracefulSignal {
+    pub const ALL: [GracefulSignal; 4] = [
+        GracefulSignal::Term,
+        GracefulSignal::Int,
+        GracefulSignal::Hup,
+        GracefulSignal::Quit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GracefulSignal::Term => "SIGTERM",
+            GracefulSignal::Int => "SIGINT",
+            GracefulSignal::Hup => "SIGHUP",
+            GracefulSignal::Quit => "SIGQUIT",
+        }
+    }
+
+    pub fn number(self) -> i32 {
+        match self {
+            GracefulSignal::Term => SIGTERM,
+            GracefulSignal::Int => SIGINT,
+            GracefulSignal::Hup => SIGHUP,
+            GracefulSignal::Quit => SIGQUIT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EscalationStep {
+    pub signal: GracefulSignal,
+    pub wait_ms: u64,
+}
+
+/// What "terminate" means for a process: send each step's signal and wait up
+/// to `wait_ms` for it to exit, then either ask before SIGKILL or send it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EscalationPolicy {
+    pub process_name: String,
+    pub steps: Vec<EscalationStep>,
+    pub confirm_sigkill: bool,
+}
+
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        Self {
+            process_name: String::new(),
+            steps: vec![EscalationStep {
+                signal: GracefulSignal::Term,
+                wait_ms: 1500,
+            }],
+            confirm_sigkill: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EscalationRules {
+    pub default: EscalationPolicy,
+    pub overrides: Vec<EscalationPolicy>,
+}
+
+impl Default for EscalationRules {
+    fn default() -> Self {
+        Self {
+            default: EscalationPolicy::default(),
+            overrides: vec![
+                EscalationPolicy {
+                    process_name: "postgres".to_string(),
+                    steps: vec![
+                        EscalationStep {
+                            signal: GracefulSignal::Term,
+                            wait_ms: 10_000,
+                        },
+                        EscalationStep {
+                            signal: GracefulSignal::Int,
+                            wait_ms: 10_000,
+                        },
+                    ],
+                    confirm_sigkill: true,
+                },
+                // Shells usually exit on hangup; an interactive one that
+                // does not is never SIGKILLed without asking.
+                EscalationPolicy {
+                    process_name: "bash".to_string(),
+                    steps: vec![EscalationStep {
+                        signal: GracefulSignal::Hup,
+                        wait_ms: 300,
+                    }],
+                    confirm_sigkill: true,
+                },
+            ],
+        }
+    }
+}
+
+impl EscalationRules {
+    /// Returns the override whose `process_name` matches `name` exactly, or
+    /// the default policy.
+    pub fn policy_for(&self, name: &str) -> &EscalationPolicy {
+        self.overrides
+            .iter()
+            .find(|policy| policy.process_name == name)
+            .unwrap_or(&self.default)
+    }
+}
@@ -1,28 +1,139 @@
-// Global hotkey support using objc2
-// Note: This is a simplified implementation using polling rather than callbacks
-// due to the complexity of objc2's block system
+// Global hotkey. On Linux the combination is grabbed system-wide through X11
+// or, under Wayland, the XDG GlobalShortcuts portal; both run on their own
+// thread and wake the UI when pressed. Elsewhere the binding only works while
+// the memwatch window has focus.
 
+use crate::keybindings::KeyBinding;
+use eframe::egui;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-pub struct HotkeyManager {
+/// Handle given to backend threads: sets the flag and wakes the UI, which may
+/// be hidden and refreshing only every few seconds.
+#[derive(Clone)]
+pub struct HotkeyTrigger {
     triggered: Arc<AtomicBool>,
+    ctx: egui::Context,
+}
+
+impl HotkeyTrigger {
+    pub fn new(ctx: &egui::Context) -> Self {
+        Self {
+            triggered: Arc::new(AtomicBool::new(false)),
+            ctx: ctx.clone(),
+        }
+    }
+
+    pub fn fire(&self) {
+        self.triggered.store(true, Ordering::Relaxed);
+        self.ctx.request_repaint();
+    }
+
+    /// True once for each run of presses since the last call.
+    pub fn take(&self) -> bool {
+        self.triggered.swap(false, Ordering::Relaxed)
+    }
+}
+
+enum Backend {
+    #[cfg(target_os = "linux")]
+    X11(crate::x11_hotkey::X11Hotkey),
+    #[cfg(target_os = "linux")]
+    Portal(crate::portal_hotkey::PortalHotkey),
+}
+
+pub struct HotkeyManager {
+    trigger: HotkeyTrigger,
+    binding: Option<KeyBinding>,
+    backend: Option<Backend>,
+    error: Option<String>,
 }
 
 impl HotkeyManager {
-    pub fn new() -> Option<Self> {
-        // Create a simple flag-based system
-        // The actual hotkey detection will be handled by egui's event system
+    pub fn new(ctx: &egui::Context) -> Option<Self> {
         Some(HotkeyManager {
-            triggered: Arc::new(AtomicBool::new(false)),
+            trigger: HotkeyTrigger::new(ctx),
+            binding: None,
+            backend: None,
+            error: None,
         })
     }
 
     pub fn check_triggered(&self) -> bool {
-        self.triggered.swap(false, Ordering::Relaxed)
+        self.trigger.take()
     }
 
-    pub fn trigger(&self) {
-        self.triggered.store(true, Ordering::Relaxed);
+    /// Registers `binding` system-wide, replacing the previous one; `None`
+    /// releases it. Does nothing if the binding is unchanged.
+    pub fn set_binding(&mut self, binding: Option<KeyBinding>) {
+        if binding == self.binding {
+            return;
+        }
+        self.binding = binding;
+        self.error = None;
+
+        // The portal session outlives binding changes: the desktop owns the
+        // actual key, and the preferred trigger only applies on first bind.
+        #[cfg(target_os = "linux")]
+        if let (Some(_), Some(Backend::Portal(_))) = (binding, &self.backend) {
+            return;
+        }
+        self.backend = None;
+
+        let Some(binding) = binding else {
+            return;
+        };
+        match Self::register(binding, self.trigger.clone()) {
+            Ok(backend) => self.backend = Some(backend),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn register(binding: KeyBinding, trigger: HotkeyTrigger) -> Result<Backend, String> {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            return Ok(Backend::Portal(crate::portal_hotkey::PortalHotkey::register(binding, trigger)));
+        }
+        if std::env::var_os("DISPLAY").is_some() {
+            return crate::x11_hotkey::X11Hotkey::register(binding, trigger).map(Backend::X11);
+        }
+        Err("no X11 or Wayland display".to_string())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn register(_binding: KeyBinding, _trigger: HotkeyTrigger) -> Result<Backend, String> {
+        Err("not supported on this platform".to_string())
+    }
+
+    /// True while a backend delivers presses from other applications, in
+    /// which case memwatch's own window must not handle the key as well.
+    pub fn is_global(&self) -> bool {
+        match &self.backend {
+            #[cfg(target_os = "linux")]
+            Some(Backend::X11(_)) => true,
+            #[cfg(target_os = "linux")]
+            Some(Backend::Portal(portal)) => portal.status().is_ok(),
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => false,
+            None => false,
+        }
+    }
+
+    /// One line for Settings describing where the hotkey works.
+    pub fn describe(&self) -> String {
+        let only_focused = "works only while memwatch has focus";
+        match (&self.backend, &self.error) {
+            #[cfg(target_os = "linux")]
+            (Some(Backend::X11(grab)), _) => format!("Registered system-wide through X11 as {}", grab.binding()),
+            #[cfg(target_os = "linux")]
+            (Some(Backend::Portal(portal)), _) => match portal.status() {
+                Ok(trigger) => format!("Registered with the desktop portal as {}", trigger),
+                Err(e) => format!("Desktop portal: {}; {}", e, only_focused),
+            },
+            #[cfg(not(target_os = "linux"))]
+            (Some(_), _) => unreachable!(),
+            (None, Some(e)) => format!("Global registration failed ({}); {}", e, only_focused),
+            (None, None) => format!("Not registered; {}", only_focused),
+        }
     }
 }
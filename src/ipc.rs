@@ -1,5 +1,6 @@
 use std::fs;
 use std::io::{Read, Write};
+use std::os::unix::fs::PermissionsExt;
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::PathBuf;
 use std::sync::mpsc::{channel, Receiver};
@@ -16,17 +17,17 @@ impl IpcServer {
         let _ = fs::remove_file(&socket_path);
 
         let listener = UnixListener::bind(&socket_path).ok()?;
+        // Only the owner may send commands.
+        fs::set_permissions(&socket_path, fs::Permissions::from_mode(0o600)).ok()?;
 
         let (tx, rx) = channel();
 
         thread::spawn(move || {
-            for stream in listener.incoming() {
-                if let Ok(mut stream) = stream {
-                    let mut buffer = [0u8; 1024];
-                    if let Ok(n) = stream.read(&mut buffer) {
-                        if let Ok(msg) = String::from_utf8(buffer[..n].to_vec()) {
-                            let _ = tx.send(msg.trim().to_string());
-                        }
+            for mut stream in listener.incoming().flatten() {
+                let mut buffer = [0u8; 1024];
+                if let Ok(n) = stream.read(&mut buffer) {
+                    if let Ok(msg) = String::from_utf8(buffer[..n].to_vec()) {
+                        let _ = tx.send(msg.trim().to_string());
                     }
                 }
             }
@@ -45,10 +46,14 @@ impl IpcServer {
 }
 
 pub fn send_toggle_command() -> Result<(), Box<dyn std::error::Error>> {
+    send_command("toggle")
+}
+
+pub fn send_command(command: &str) -> Result<(), Box<dyn std::error::Error>> {
     let socket_path = PathBuf::from("/tmp/memwatch.sock");
 
     let mut stream = UnixStream::connect(&socket_path)?;
-    stream.write_all(b"toggle")?;
+    stream.write_all(command.as_bytes())?;
 
     Ok(())
 }
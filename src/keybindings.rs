@@ -0,0 +1,353 @@
+// In-app keyboard shortcuts. Bindings are stored as strings such as
+// "Ctrl+Shift+K" so settings.json stays readable, and parsed into a keymap
+// whenever settings change.
+
+use eframe::egui::{Key, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    ToggleWindow,
+    CommandPalette,
+    FocusFilter,
+    SelectPrevious,
+    SelectNext,
+    PageUp,
+    PageDown,
+    SelectFirst,
+    SelectLast,
+    SortMemory,
+    SortCpu,
+    SortDiskIo,
+    SortOomScore,
+    Terminate,
+    ForceKill,
+    SignalMenu,
+    ToggleTree,
+    OpenDetails,
+    CloseDialog,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 19] = [
+        KeyAction::ToggleWindow,
+        KeyAction::CommandPalette,
+        KeyAction::FocusFilter,
+        KeyAction::SelectPrevious,
+        KeyAction::SelectNext,
+        KeyAction::PageUp,
+        KeyAction::PageDown,
+        KeyAction::SelectFirst,
+        KeyAction::SelectLast,
+        KeyAction::SortMemory,
+        KeyAction::SortCpu,
+        KeyAction::SortDiskIo,
+        KeyAction::SortOomScore,
+        KeyAction::Terminate,
+        KeyAction::ForceKill,
+        KeyAction::SignalMenu,
+        KeyAction::ToggleTree,
+        KeyAction::OpenDetails,
+        KeyAction::CloseDialog,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyAction::ToggleWindow => "Show / hide window",
+            KeyAction::CommandPalette => "Command palette",
+            KeyAction::FocusFilter => "Focus filter",
+            KeyAction::SelectPrevious => "Select previous",
+            KeyAction::SelectNext => "Select next",
+            KeyAction::PageUp => "Page up",
+            KeyAction::PageDown => "Page down",
+            KeyAction::SelectFirst => "Select first",
+            KeyAction::SelectLast => "Select last",
+            KeyAction::SortMemory => "Sort by memory",
+            KeyAction::SortCpu => "Sort by CPU",
+            KeyAction::SortDiskIo => "Sort by disk I/O",
+            KeyAction::SortOomScore => "Sort by OOM score",
+            KeyAction::Terminate => "Terminate selected",
+            KeyAction::ForceKill => "Force kill selected",
+            KeyAction::SignalMenu => "Send signal…",
+            KeyAction::ToggleTree => "Toggle tree view",
+            KeyAction::OpenDetails => "Open details",
+            KeyAction::CloseDialog => "Close dialog",
+        }
+    }
+}
+
+/// A key plus the modifiers that must be held with it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyBinding {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyBinding {
+    /// Parses strings like "K", "Shift+K", "Ctrl+Alt+Delete" or "/". `Cmd`
+    /// is ⌘ on macOS and Ctrl elsewhere; names are case-insensitive.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (modifier_text, key_text) = match text.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+
+        let mut modifiers = Modifiers::NONE;
+        for part in modifier_text.split('+').filter(|part| !part.is_empty()) {
+            modifiers = modifiers
+                | match part.trim().to_lowercase().as_str() {
+                    "ctrl" | "control" => Modifiers::CTRL,
+                    "shift" => Modifiers::SHIFT,
+                    "alt" | "option" | "opt" => Modifiers::ALT,
+                    "cmd" | "command" | "super" | "meta" => Modifiers::COMMAND,
+                    _ => return Err(format!("unknown modifier `{}` in \"{}\"", part.trim(), text)),
+                };
+        }
+
+        let key_text = key_text.trim();
+        let key = Key::from_name(key_text)
+            .or_else(|| Key::ALL.iter().copied().find(|key| key.name().eq_ignore_ascii_case(key_text)))
+            .ok_or_else(|| format!("unknown key `{}` in \"{}\"", key_text, text))?;
+        Ok(Self { key, modifiers })
+    }
+
+    pub fn matches(&self, key: Key, pressed: Modifiers) -> bool {
+        self.key == key && pressed.matches_exact(self.modifiers)
+    }
+
+    /// Whether a single key press could trigger both bindings.
+    pub fn overlaps(&self, other: &KeyBinding) -> bool {
+        self.key == other.key && modifier_flags(self.modifiers) == modifier_flags(other.modifiers)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (ctrl, shift, alt, cmd) = modifier_flags(self.modifiers);
+        for (held, name) in [(ctrl, "Ctrl"), (alt, "Alt"), (shift, "Shift"), (cmd, "Cmd")] {
+            if held {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.key.name())
+    }
+}
+
+/// Ctrl, Shift, Alt and ⌘ as the current platform distinguishes them:
+/// `Cmd` and `Ctrl` are the same key outside macOS.
+fn modifier_flags(modifiers: Modifiers) -> (bool, bool, bool, bool) {
+    if cfg!(target_os = "macos") {
+        (modifiers.ctrl, modifiers.shift, modifiers.alt, modifiers.command || modifiers.mac_cmd)
+    } else {
+        (modifiers.ctrl || modifiers.command, modifiers.shift, modifiers.alt, false)
+    }
+}
+
+/// Binding strings as saved in settings.json.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub toggle_window: String,
+    pub command_palette: String,
+    pub focus_filter: String,
+    pub select_previous: String,
+    pub select_next: String,
+    pub page_up: String,
+    pub page_down: String,
+    pub select_first: String,
+    pub select_last: String,
+    pub sort_memory: String,
+    pub sort_cpu: String,
+    pub sort_disk_io: String,
+    pub sort_oom_score: String,
+    pub terminate: String,
+    pub force_kill: String,
+    pub signal_menu: String,
+    pub toggle_tree: String,
+    pub open_details: String,
+    pub close_dialog: String,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            toggle_window: "Alt+Cmd+M".to_string(),
+            command_palette: "Cmd+K".to_string(),
+            focus_filter: "/".to_string(),
+            select_previous: "Up".to_string(),
+            select_next: "Down".to_string(),
+            page_up: "PageUp".to_string(),
+            page_down: "PageDown".to_string(),
+            select_first: "Home".to_string(),
+            select_last: "End".to_string(),
+            sort_memory: "M".to_string(),
+            sort_cpu: "C".to_string(),
+            sort_disk_io: "I".to_string(),
+            sort_oom_score: "O".to_string(),
+            terminate: "K".to_string(),
+            force_kill: "Shift+K".to_string(),
+            signal_menu: "S".to_string(),
+            toggle_tree: "T".to_string(),
+            open_details: "Enter".to_string(),
+            close_dialog: "Escape".to_string(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn get(&self, action: KeyAction) -> &String {
+        match action {
+            KeyAction::ToggleWindow => &self.toggle_window,
+            KeyAction::CommandPalette => &self.command_palette,
+            KeyAction::FocusFilter => &self.focus_filter,
+            KeyAction::SelectPrevious => &self.select_previous,
+            KeyAction::SelectNext => &self.select_next,
+            KeyAction::PageUp => &self.page_up,
+            KeyAction::PageDown => &self.page_down,
+            KeyAction::SelectFirst => &self.select_first,
+            KeyAction::SelectLast => &self.select_last,
+            KeyAction::SortMemory => &self.sort_memory,
+            KeyAction::SortCpu => &self.sort_cpu,
+            KeyAction::SortDiskIo => &self.sort_disk_io,
+            KeyAction::SortOomScore => &self.sort_oom_score,
+            KeyAction::Terminate => &self.terminate,
+            KeyAction::ForceKill => &self.force_kill,
+            KeyAction::SignalMenu => &self.signal_menu,
+            KeyAction::ToggleTree => &self.toggle_tree,
+            KeyAction::OpenDetails => &self.open_details,
+            KeyAction::CloseDialog => &self.close_dialog,
+        }
+    }
+
+    pub fn get_mut(&mut self, action: KeyAction) -> &mut String {
+        match action {
+            KeyAction::ToggleWindow => &mut self.toggle_window,
+            KeyAction::CommandPalette => &mut self.command_palette,
+            KeyAction::FocusFilter => &mut self.focus_filter,
+            KeyAction::SelectPrevious => &mut self.select_previous,
+            KeyAction::SelectNext => &mut self.select_next,
+            KeyAction::PageUp => &mut self.page_up,
+            KeyAction::PageDown => &mut self.page_down,
+            KeyAction::SelectFirst => &mut self.select_first,
+            KeyAction::SelectLast => &mut self.select_last,
+            KeyAction::SortMemory => &mut self.sort_memory,
+            KeyAction::SortCpu => &mut self.sort_cpu,
+            KeyAction::SortDiskIo => &mut self.sort_disk_io,
+            KeyAction::SortOomScore => &mut self.sort_oom_score,
+            KeyAction::Terminate => &mut self.terminate,
+            KeyAction::ForceKill => &mut self.force_kill,
+            KeyAction::SignalMenu => &mut self.signal_menu,
+            KeyAction::ToggleTree => &mut self.toggle_tree,
+            KeyAction::OpenDetails => &mut self.open_details,
+            KeyAction::CloseDialog => &mut self.close_dialog,
+        }
+    }
+
+    /// Parses every binding. An empty string leaves the action unbound;
+    /// unparseable ones are skipped and reported.
+    pub fn keymap(&self) -> (Vec<(KeyAction, KeyBinding)>, Vec<String>) {
+        let mut keymap = Vec::new();
+        let mut errors = Vec::new();
+        for action in KeyAction::ALL {
+            let text = self.get(action);
+            if text.trim().is_empty() {
+                continue;
+            }
+            match KeyBinding::parse(text) {
+                Ok(binding) => keymap.push((action, binding)),
+                Err(e) => errors.push(format!("{}: {}", action.label(), e)),
+            }
+        }
+        (keymap, errors)
+    }
+
+    /// Pairs of actions bound to the same key combination.
+    pub fn conflicts(&self) -> Vec<String> {
+        let (keymap, _) = self.keymap();
+        let mut conflicts = Vec::new();
+        for (i, (action, binding)) in keymap.iter().enumerate() {
+            for (other, other_binding) in &keymap[i + 1..] {
+                if binding.overlaps(other_binding) {
+                    conflicts.push(format!(
+                        "{} and {} are both bound to {}",
+                        action.label(),
+                        other.label(),
+                        binding
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Resets bindings that do not parse to their defaults and reports
+    /// conflicts, one warning each.
+    pub fn validate(&mut self) -> Vec<String> {
+        let defaults = KeyBindings::default();
+        let mut warnings = Vec::new();
+        for action in KeyAction::ALL {
+            let text = self.get(action);
+            if text.trim().is_empty() {
+                continue;
+            }
+            if let Err(e) = KeyBinding::parse(text) {
+                let default = defaults.get(action).clone();
+                warnings.push(format!("Key binding for {}: {}; using \"{}\"", action.label(), e, default));
+                *self.get_mut(action) = default;
+            }
+        }
+        warnings.extend(self.conflicts().into_iter().map(|c| format!("Key binding conflict: {}", c)));
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modifiers_and_key_names() {
+        let binding = KeyBinding::parse("Ctrl+Shift+K").unwrap();
+        assert_eq!(binding.key, Key::K);
+        assert_eq!(binding.modifiers, Modifiers::CTRL | Modifiers::SHIFT);
+
+        assert_eq!(KeyBinding::parse("/").unwrap().key, Key::Slash);
+        assert_eq!(KeyBinding::parse("ctrl++").unwrap().key, Key::Plus);
+        assert_eq!(KeyBinding::parse("pagedown").unwrap().key, Key::PageDown);
+        assert_eq!(KeyBinding::parse("k").unwrap().modifiers, Modifiers::NONE);
+        assert!(KeyBinding::parse("Hyper+K").is_err());
+        assert!(KeyBinding::parse("Ctrl+Banana").is_err());
+    }
+
+    #[test]
+    fn exact_modifiers_keep_k_and_shift_k_apart() {
+        let terminate = KeyBinding::parse("K").unwrap();
+        let force_kill = KeyBinding::parse("Shift+K").unwrap();
+        assert!(terminate.matches(Key::K, Modifiers::NONE));
+        assert!(!terminate.matches(Key::K, Modifiers::SHIFT));
+        assert!(force_kill.matches(Key::K, Modifiers::SHIFT));
+        assert!(!terminate.overlaps(&force_kill));
+    }
+
+    #[test]
+    fn defaults_have_no_conflicts() {
+        let mut bindings = KeyBindings::default();
+        assert!(bindings.validate().is_empty());
+    }
+
+    #[test]
+    fn conflicts_and_bad_bindings_are_reported() {
+        let mut bindings = KeyBindings {
+            signal_menu: "shift+k".to_string(),
+            toggle_tree: "Ctrl+Nope".to_string(),
+            ..KeyBindings::default()
+        };
+        let warnings = bindings.validate();
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].contains("Toggle tree view"));
+        assert!(warnings[1].contains("Force kill selected and Send signal…"));
+        assert_eq!(bindings.toggle_tree, "T");
+    }
+}
@@ -1,7 +1,14 @@
-use libc::{kill, SIGKILL, SIGTERM};
+use crate::audit::{self, AuditEntry, KillOrigin};
+use crate::escalation::{EscalationPolicy, EscalationRules};
+use crate::protection::ProtectionRules;
+use libc::{kill, SIGCONT, SIGKILL, SIGSTOP, SIGTERM};
+use std::fmt;
 use std::thread;
-use std::time::Duration;
-use sysinfo::{Pid, System};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, System, UpdateKind, Users};
+
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const SIGKILL_WAIT: Duration = Duration::from_millis(1000);
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum KillStatus {
@@ -9,62 +16,382 @@ pub enum KillStatus {
     RequiresConfirmation(u32),
     Failed(String),
     NotFound,
+    Refused(String),
 }
 
-pub fn terminate_process(pid: u32) -> KillStatus {
-    let pid_i32 = pid as i32;
+impl fmt::Display for KillStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KillStatus::Success => write!(f, "Success"),
+            KillStatus::RequiresConfirmation(_) => write!(f, "Still running"),
+            KillStatus::Failed(err) => write!(f, "Failed: {}", err),
+            KillStatus::NotFound => write!(f, "Not found"),
+            KillStatus::Refused(reason) => write!(f, "Refused: {}", reason),
+        }
+    }
+}
 
-    let mut system = System::new();
-    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+/// Who is asking for a signal, under which protection rules and with which
+/// escalation policies. A `None` guard skips the protection check and must
+/// only follow an explicit user override.
+pub struct KillContext<'a> {
+    pub guard: Option<&'a ProtectionRules>,
+    pub escalation: &'a EscalationRules,
+    pub origin: KillOrigin,
+}
 
-    if !system.processes().contains_key(&Pid::from_u32(pid)) {
-        return KillStatus::NotFound;
+/// Details captured before signalling so the audit log still has them after
+/// the process is gone.
+struct Target {
+    pid: u32,
+    name: String,
+    command_line: String,
+    user: String,
+    protected: Option<String>,
+}
+
+/// Runs the escalation policy for the process name: each step's signal is
+/// followed by polling for exit, so a process that quits early is reported
+/// immediately. Ends with SIGKILL, or `RequiresConfirmation` if the policy
+/// asks for it.
+pub fn terminate_process(pid: u32, ctx: &KillContext) -> KillStatus {
+    signal_and_wait(&[pid], ctx, false).remove(0).1
+}
+
+pub fn force_kill_process(pid: u32, ctx: &KillContext) -> KillStatus {
+    signal_and_wait(&[pid], ctx, true).remove(0).1
+}
+
+/// Sends a single SIGTERM without waiting for the process to exit, for callers
+/// that must not block.
+pub fn send_sigterm(pid: u32, ctx: &KillContext) -> KillStatus {
+    send_signal(pid, SIGTERM, "SIGTERM", ctx)
+}
+
+pub fn stop_process(pid: u32, ctx: &KillContext) -> KillStatus {
+    send_signal(pid, SIGSTOP, "SIGSTOP", ctx)
+}
+
+pub fn continue_process(pid: u32, ctx: &KillContext) -> KillStatus {
+    send_signal(pid, SIGCONT, "SIGCONT", ctx)
+}
+
+/// Terminates every PID at once, so a batch costs the longest grace period
+/// instead of the sum of them.
+pub fn terminate_processes(pids: &[u32], ctx: &KillContext) -> Vec<(u32, KillStatus)> {
+    signal_and_wait(pids, ctx, false)
+}
+
+pub fn force_kill_processes(pids: &[u32], ctx: &KillContext) -> Vec<(u32, KillStatus)> {
+    signal_and_wait(pids, ctx, true)
+}
+
+/// Where a process is in its escalation policy.
+#[derive(Clone, Copy)]
+enum Stage {
+    Step(usize),
+    Kill,
+}
+
+/// A process still being waited on, and when its current wait ends.
+struct Pending<'a> {
+    index: usize,
+    target: Target,
+    policy: &'a EscalationPolicy,
+    stage: Stage,
+    deadline: Instant,
+}
+
+impl Pending<'_> {
+    fn signal(&self) -> (i32, &'static str, Duration) {
+        match self.stage {
+            Stage::Step(i) => {
+                let step = &self.policy.steps[i];
+                (step.signal.number(), step.signal.name(), Duration::from_millis(step.wait_ms))
+            }
+            Stage::Kill => (SIGKILL, "SIGKILL", SIGKILL_WAIT),
+        }
     }
 
-    unsafe {
-        let result = kill(pid_i32, SIGTERM);
-        if result != 0 {
-            let error = std::io::Error::last_os_error();
-            return KillStatus::Failed(format!("Failed to send SIGTERM: {}", error));
+    /// Moves to `stage` and sends its signal. Returns the final status if
+    /// there is nothing left to wait for; `None` as the stage means the
+    /// policy wants SIGKILL confirmed first.
+    fn enter(&mut self, stage: Option<Stage>, origin: KillOrigin) -> Option<KillStatus> {
+        let Some(stage) = stage else {
+            return Some(KillStatus::RequiresConfirmation(self.target.pid));
+        };
+        self.stage = stage;
+        let (signal, name, wait) = self.signal();
+        if let Some(status) = signal_target(&self.target, signal, name) {
+            log_attempt(&self.target, name, &status, origin);
+            return Some(status);
         }
+        self.deadline = Instant::now() + wait;
+        None
+    }
+
+    /// Called when the current wait ran out with the process still alive.
+    fn advance(&mut self, origin: KillOrigin) -> Option<KillStatus> {
+        let (_, name, _) = self.signal();
+        match self.stage {
+            Stage::Kill => {
+                let status = KillStatus::Failed("Process still running after SIGKILL".to_string());
+                log_attempt(&self.target, name, &status, origin);
+                Some(status)
+            }
+            Stage::Step(i) => {
+                log_attempt(&self.target, name, &KillStatus::RequiresConfirmation(self.target.pid), origin);
+                self.enter(stage_after(self.policy, Some(i)), origin)
+            }
+        }
+    }
+}
+
+/// The stage following step `done` (or the first one), or `None` if SIGKILL
+/// needs confirmation.
+fn stage_after(policy: &EscalationPolicy, done: Option<usize>) -> Option<Stage> {
+    let next = done.map_or(0, |i| i + 1);
+    if next < policy.steps.len() {
+        Some(Stage::Step(next))
+    } else if policy.confirm_sigkill {
+        None
+    } else {
+        Some(Stage::Kill)
     }
+}
 
-    thread::sleep(Duration::from_millis(1500));
+/// Runs every PID's escalation policy, or straight SIGKILL with `force`, in
+/// a single loop: each process is signalled as its steps come due and all of
+/// them are polled together, with no thread per process.
+fn signal_and_wait(pids: &[u32], ctx: &KillContext, force: bool) -> Vec<(u32, KillStatus)> {
+    let mut system = System::new();
+    let all: Vec<Pid> = pids.iter().map(|&pid| Pid::from_u32(pid)).collect();
+    refresh_targets(&mut system, &all);
+    let users = Users::new_with_refreshed_list();
 
-    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+    let mut results: Vec<Option<KillStatus>> = vec![None; pids.len()];
+    let mut pending = Vec::new();
+    for (index, &pid) in pids.iter().enumerate() {
+        let Some(target) = lookup(&system, &users, pid, ctx) else {
+            results[index] = Some(KillStatus::NotFound);
+            continue;
+        };
+        let policy = ctx.escalation.policy_for(&target.name);
+        let mut process = Pending {
+            index,
+            target,
+            policy,
+            stage: Stage::Kill,
+            deadline: Instant::now(),
+        };
+        let first = if force { Some(Stage::Kill) } else { stage_after(policy, None) };
+        match process.enter(first, ctx.origin) {
+            Some(status) => results[index] = Some(status),
+            None => pending.push(process),
+        }
+    }
 
-    if !system.processes().contains_key(&Pid::from_u32(pid)) {
-        return KillStatus::Success;
+    while !pending.is_empty() {
+        let watched: Vec<Pid> = pending.iter().map(|p| Pid::from_u32(p.target.pid)).collect();
+        system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&watched), true);
+        let now = Instant::now();
+        pending.retain_mut(|process| {
+            let status = if has_exited(&system, process.target.pid) {
+                let (_, name, _) = process.signal();
+                log_attempt(&process.target, name, &KillStatus::Success, ctx.origin);
+                Some(KillStatus::Success)
+            } else if now >= process.deadline {
+                process.advance(ctx.origin)
+            } else {
+                None
+            };
+            match status {
+                Some(status) => {
+                    results[process.index] = Some(status);
+                    false
+                }
+                None => true,
+            }
+        });
+        if let Some(next) = pending.iter().map(|p| p.deadline).min() {
+            thread::sleep(EXIT_POLL_INTERVAL.min(next.saturating_duration_since(Instant::now())));
+        }
     }
 
-    KillStatus::RequiresConfirmation(pid)
+    pids.iter()
+        .zip(results)
+        .map(|(&pid, status)| (pid, status.unwrap_or(KillStatus::NotFound)))
+        .collect()
 }
 
-pub fn force_kill_process(pid: u32) -> KillStatus {
-    let pid_i32 = pid as i32;
-
+fn send_signal(pid: u32, signal: i32, signal_name: &str, ctx: &KillContext) -> KillStatus {
     let mut system = System::new();
-    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+    refresh_targets(&mut system, &[Pid::from_u32(pid)]);
 
-    if !system.processes().contains_key(&Pid::from_u32(pid)) {
+    let Some(target) = lookup(&system, &Users::new_with_refreshed_list(), pid, ctx) else {
         return KillStatus::NotFound;
+    };
+
+    let status = signal_target(&target, signal, signal_name).unwrap_or(KillStatus::Success);
+    log_attempt(&target, signal_name, &status, ctx.origin);
+    status
+}
+
+/// Sends `signal` unless the target is protected. Returns `None` once the
+/// signal was delivered, or the status to report instead.
+fn signal_target(target: &Target, signal: i32, signal_name: &str) -> Option<KillStatus> {
+    if let Some(reason) = &target.protected {
+        return Some(KillStatus::Refused(reason.clone()));
     }
 
     unsafe {
-        let result = kill(pid_i32, SIGKILL);
+        let result = kill(target.pid as i32, signal);
         if result != 0 {
             let error = std::io::Error::last_os_error();
-            return KillStatus::Failed(format!("Failed to send SIGKILL: {}", error));
+            return Some(KillStatus::Failed(format!("Failed to send {}: {}", signal_name, error)));
         }
     }
+    None
+}
 
-    thread::sleep(Duration::from_millis(200));
+/// Whether the process is gone, or a zombie waiting to be reaped.
+fn has_exited(system: &System, pid: u32) -> bool {
+    match system.process(Pid::from_u32(pid)) {
+        None => true,
+        Some(process) => process.status() == ProcessStatus::Zombie,
+    }
+}
 
-    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+fn refresh_targets(system: &mut System, pids: &[Pid]) {
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(pids),
+        true,
+        ProcessRefreshKind::new()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_user(UpdateKind::OnlyIfNotSet),
+    );
+}
 
-    if !system.processes().contains_key(&Pid::from_u32(pid)) {
-        KillStatus::Success
-    } else {
-        KillStatus::Failed("Process still running after SIGKILL".to_string())
+fn lookup(system: &System, users: &Users, pid: u32, ctx: &KillContext) -> Option<Target> {
+    let process = system.process(Pid::from_u32(pid))?;
+
+    let name = process.name().to_string_lossy().to_string();
+    let exe = process.exe().map(|path| path.to_string_lossy().to_string());
+    let uid = process.user_id().map(|uid| **uid);
+    let protected = ctx
+        .guard
+        .and_then(|rules| rules.reason(pid, &name, exe.as_deref(), uid));
+
+    let command_line = process
+        .cmd()
+        .iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let user = process
+        .user_id()
+        .and_then(|uid| users.get_user_by_id(uid).map(|user| user.name().to_string()))
+        .or_else(|| uid.map(|uid| uid.to_string()))
+        .unwrap_or_default();
+
+    Some(Target {
+        pid,
+        name,
+        command_line,
+        user,
+        protected,
+    })
+}
+
+fn log_attempt(target: &Target, signal_name: &str, status: &KillStatus, origin: KillOrigin) {
+    let _ = audit::record(&AuditEntry {
+        timestamp: audit::unix_now(),
+        pid: target.pid,
+        name: target.name.clone(),
+        command_line: target.command_line.clone(),
+        user: target.user.clone(),
+        signal: signal_name.to_string(),
+        status: status.to_string(),
+        origin,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escalation::{EscalationStep, GracefulSignal};
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{Child, Command};
+
+    fn rules(confirm_sigkill: bool) -> EscalationRules {
+        EscalationRules {
+            default: EscalationPolicy {
+                process_name: String::new(),
+                steps: vec![EscalationStep {
+                    signal: GracefulSignal::Term,
+                    wait_ms: 300,
+                }],
+                confirm_sigkill,
+            },
+            overrides: Vec::new(),
+        }
+    }
+
+    fn terminate(child: &Child, rules: &EscalationRules) -> KillStatus {
+        let ctx = KillContext {
+            guard: None,
+            escalation: rules,
+            origin: KillOrigin::Gui,
+        };
+        terminate_process(child.id(), &ctx)
+    }
+
+    /// A `sleep` that ignores SIGTERM. Waits for the exec so the trap is in
+    /// place before the test signals it.
+    fn spawn_term_ignoring() -> Child {
+        let child = Command::new("sh")
+            .args(["-c", "trap '' TERM; exec sleep 30"])
+            .spawn()
+            .unwrap();
+        let comm = format!("/proc/{}/comm", child.id());
+        let started = Instant::now();
+        while std::fs::read_to_string(&comm).unwrap_or_default().trim() != "sleep" {
+            assert!(started.elapsed() < Duration::from_secs(5), "child never exec'd sleep");
+            thread::sleep(Duration::from_millis(10));
+        }
+        child
+    }
+
+    #[test]
+    fn term_ends_a_cooperative_process() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let started = Instant::now();
+        assert_eq!(terminate(&child, &rules(false)), KillStatus::Success);
+        // The exited child stays a zombie until reaped here, which must
+        // already count as gone instead of waiting out the step.
+        assert!(started.elapsed() < Duration::from_millis(300));
+        assert_eq!(child.wait().unwrap().signal(), Some(SIGTERM));
+    }
+
+    #[test]
+    fn ignored_term_escalates_to_sigkill() {
+        let mut child = spawn_term_ignoring();
+        let started = Instant::now();
+        assert_eq!(terminate(&child, &rules(false)), KillStatus::Success);
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert_eq!(child.wait().unwrap().signal(), Some(SIGKILL));
+    }
+
+    #[test]
+    fn confirm_sigkill_stops_before_sigkill() {
+        let mut child = spawn_term_ignoring();
+        assert_eq!(
+            terminate(&child, &rules(true)),
+            KillStatus::RequiresConfirmation(child.id())
+        );
+        assert_eq!(child.try_wait().unwrap(), None);
+        child.kill().unwrap();
+        child.wait().unwrap();
     }
 }
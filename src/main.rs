@@ -1,9 +1,32 @@
+mod actions;
+mod audit;
+mod bench;
+mod escalation;
 mod hotkey;
 mod ipc;
+mod keybindings;
 mod killer;
 mod metrics;
+mod oom_guard;
+mod palette;
+#[cfg(target_os = "linux")]
+mod portal_hotkey;
+mod power;
+mod priority;
+mod proc_events;
+#[cfg(target_os = "linux")]
+mod procfs;
+mod protection;
+mod scheduler;
+mod self_monitor;
 mod settings;
+mod snapshot;
+mod source;
+mod sockets;
 mod ui;
+mod users;
+#[cfg(target_os = "linux")]
+mod x11_hotkey;
 
 use std::env;
 
@@ -19,15 +42,53 @@ fn main() {
                 }
                 return;
             }
+            "history" => {
+                let limit = args.get(2).and_then(|n| n.parse::<usize>().ok()).unwrap_or(50);
+                print_history(limit);
+                return;
+            }
+            "--profile" => {
+                let Some(name) = args.get(2) else {
+                    eprintln!("Usage: memwatch --profile NAME");
+                    std::process::exit(1);
+                };
+                // Switch a running instance if there is one, otherwise
+                // start with the profile applied.
+                if ipc::send_command(&format!("profile {}", name)).is_ok() {
+                    return;
+                }
+                let (mut settings, _) = settings::Settings::load();
+                if let Err(e) = settings.switch_profile(name) {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+                let _ = settings.save();
+            }
+            "config" => {
+                std::process::exit(run_config_command(&args[2..]));
+            }
+            "bench" => {
+                std::process::exit(bench::run(&args[2..]));
+            }
             "--help" | "-h" => {
                 println!("memwatch - macOS process monitor");
                 println!();
                 println!("USAGE:");
                 println!("  memwatch          Launch the GUI");
                 println!("  memwatch toggle   Toggle window visibility");
+                println!("  memwatch --profile NAME     Switch to a settings profile (launches the GUI if needed)");
+                println!("  memwatch history [N]        Show the last N signals sent (default 50)");
+                println!("  memwatch config get [KEY]   Print settings, or one setting by dotted key");
+                println!("  memwatch config set KEY VALUE");
+                println!("                    Change a setting and apply it to a running instance");
+                println!("  memwatch config path        Print the settings file location");
+                println!("  memwatch config reset       Restore default settings");
+                println!("  memwatch bench [--spawn N] [--rounds R]");
+                println!("                    Compare process data sources with N extra idle processes");
                 println!();
                 println!("HOTKEY:");
                 println!("  ⌥⌘M              Toggle window from anywhere (Option+Command+M)");
+                println!("  ⌘K / Ctrl+K      Command palette: search actions and processes");
                 println!();
                 println!("NOTE:");
                 println!("  You can also use 'memwatch toggle' from terminal");
@@ -55,3 +116,104 @@ fn main() {
         Box::new(|cc| Ok(Box::new(ui::MemwatchApp::new(cc)))),
     );
 }
+
+fn print_history(limit: usize) {
+    let entries = audit::load();
+    if entries.is_empty() {
+        println!("No signals recorded yet.");
+        return;
+    }
+
+    println!(
+        "{:<23}  {:>7}  {:<20}  {:<12}  {:<7}  {:<4}  {:<24}  COMMAND",
+        "TIME", "PID", "NAME", "USER", "SIGNAL", "FROM", "RESULT"
+    );
+    for entry in entries.iter().rev().take(limit) {
+        println!(
+            "{:<23}  {:>7}  {:<20}  {:<12}  {:<7}  {:<4}  {:<24}  {}",
+            audit::format_timestamp(entry.timestamp),
+            entry.pid,
+            entry.name,
+            entry.user,
+            entry.signal,
+            entry.origin.label(),
+            entry.status,
+            entry.command_line
+        );
+    }
+}
+
+fn run_config_command(args: &[String]) -> i32 {
+    use settings::Settings;
+
+    let usage = || {
+        eprintln!("Usage: memwatch config get [KEY] | set KEY VALUE | path | reset");
+        1
+    };
+
+    match args.first().map(String::as_str) {
+        Some("path") => match Settings::config_path() {
+            Some(path) => {
+                println!("{}", path.display());
+                0
+            }
+            None => {
+                eprintln!("No configuration directory on this system");
+                1
+            }
+        },
+        Some("get") => {
+            let (settings, warnings) = Settings::load();
+            for warning in warnings {
+                eprintln!("memwatch: settings: {}", warning);
+            }
+            let key = args.get(1).map(String::as_str).unwrap_or("");
+            match settings.get_field(key) {
+                Some(value) => {
+                    println!("{}", serde_json::to_string_pretty(&value).unwrap_or_default());
+                    0
+                }
+                None => {
+                    eprintln!("Unknown setting `{}`", key);
+                    1
+                }
+            }
+        }
+        Some("set") => {
+            let (Some(key), Some(raw)) = (args.get(1), args.get(2)) else {
+                return usage();
+            };
+            // Bare words such as `Cpu` are taken as strings.
+            let value = serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::String(raw.clone()));
+            let (settings, _) = Settings::load();
+            match settings.with_field(key, value) {
+                Ok((updated, warnings)) => {
+                    for warning in warnings {
+                        eprintln!("memwatch: settings: {}", warning);
+                    }
+                    save_and_notify(&updated)
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    1
+                }
+            }
+        }
+        Some("reset") => save_and_notify(&Settings::default()),
+        _ => usage(),
+    }
+}
+
+/// Saves settings and asks a running instance, if any, to pick them up.
+fn save_and_notify(settings: &settings::Settings) -> i32 {
+    if let Err(e) = settings.save() {
+        eprintln!("Failed to save settings: {}", e);
+        return 1;
+    }
+    if ipc::send_command("reload-settings").is_ok() {
+        println!("Saved; applied to the running instance");
+    } else {
+        println!("Saved");
+    }
+    0
+}
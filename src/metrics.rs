@@ -1,78 +1,282 @@
-use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
-use std::time::Instant;
+use std::collections::BTreeMap;
+use crate::power::{self, BatteryStatus};
+use crate::proc_events::{ProcessSample, ProcessTracker};
+use crate::settings::ProcessSourceKind;
+use crate::source::{self, Clock, ProcessSource, SamplingCost, SystemClock, SystemSample};
+use serde::Serialize;
+use sysinfo::{Components, Disks, Networks};
+use std::time::{Duration, Instant};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ProcessInfo {
     pub pid: u32,
     pub name: String,
+    pub parent: Option<u32>,
     pub cpu_usage: f32,
     pub memory_mb: f64,
+    pub disk_read_bps: f64,
+    pub disk_write_bps: f64,
+    pub exe: Option<String>,
+    pub uid: Option<u32>,
+    /// Account name for `uid`, if it has a passwd entry.
+    pub user: Option<String>,
+    /// Session ID (see getsid(2)); `None` off Unix or once the process is gone.
+    pub session_id: Option<u32>,
+    /// Kernel OOM killer score and adjustment; always `None` off Linux.
+    pub oom_score: Option<i32>,
+    pub oom_score_adj: Option<i32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FilesystemInfo {
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    /// Total and free inodes; Linux only.
+    pub inodes: Option<(u64, u64)>,
 }
 
+impl FilesystemInfo {
+    pub fn used_percent(&self) -> f32 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        (self.total_bytes - self.available_bytes.min(self.total_bytes)) as f32 / self.total_bytes as f32 * 100.0
+    }
+
+    pub fn inode_used_percent(&self) -> Option<f32> {
+        let (total, free) = self.inodes?;
+        if total == 0 {
+            return None;
+        }
+        Some((total - free.min(total)) as f32 / total as f32 * 100.0)
+    }
+}
+
+/// One network sample: elapsed seconds, received and transmitted bytes per second.
+pub type NetworkSample = (f64, f64, f64);
+
+/// How often the mount list is re-read, so drives mounted later show up.
+const DISK_LIST_INTERVAL: Duration = Duration::from_secs(10);
+
 pub struct MetricsCollector {
-    system: System,
+    source: Box<dyn ProcessSource>,
+    clock: Box<dyn Clock>,
+    system_sample: SystemSample,
+    networks: Networks,
+    components: Components,
+    disks: Disks,
+    /// When the mount list was last read; `None` if it is never read.
+    disks_listed: Option<Instant>,
+    filesystems: Vec<FilesystemInfo>,
+    process_tracker: ProcessTracker,
+    battery: Option<BatteryStatus>,
     last_update: Instant,
     cpu_history: Vec<(f64, f32)>,
     memory_history: Vec<(f64, f64)>,
+    disk_history: Vec<(f64, f64)>,
+    network_history: BTreeMap<String, Vec<NetworkSample>>,
+    temperature_history: Vec<(f64, f32)>,
+    battery_history: Vec<(f64, f32)>,
+    filesystem_history: BTreeMap<String, Vec<(f64, f32)>>,
+    /// Seconds covered by the disk counters of the latest refresh.
+    sample_interval: f64,
+    /// Wall time the latest `refresh` took.
+    refresh_duration: Duration,
     start_time: Instant,
 }
 
 impl MetricsCollector {
-    pub fn new() -> Self {
-        let mut system = System::new_all();
-        system.refresh_all();
+    pub fn new(kind: ProcessSourceKind) -> Self {
+        Self {
+            networks: Networks::new_with_refreshed_list(),
+            components: Components::new_with_refreshed_list(),
+            disks: Disks::new_with_refreshed_list(),
+            disks_listed: Some(Instant::now()),
+            process_tracker: ProcessTracker::new(),
+            ..Self::with_source(source::create(kind), Box::new(SystemClock))
+        }
+    }
 
-        let now = Instant::now();
+    /// Collector driven by `source` and `clock` only: no network, sensor or
+    /// disk lists, and start/exit tracking by PID diff.
+    pub fn with_source(source: Box<dyn ProcessSource>, clock: Box<dyn Clock>) -> Self {
+        let now = clock.now();
         Self {
-            system,
+            system_sample: source.system(),
+            source,
+            clock,
+            networks: Networks::new(),
+            components: Components::new(),
+            disks: Disks::new(),
+            disks_listed: None,
+            filesystems: Vec::new(),
+            process_tracker: ProcessTracker::with_pid_diff(),
+            battery: None,
             last_update: now,
             cpu_history: Vec::with_capacity(300),
             memory_history: Vec::with_capacity(300),
+            disk_history: Vec::with_capacity(300),
+            network_history: BTreeMap::new(),
+            temperature_history: Vec::with_capacity(300),
+            battery_history: Vec::with_capacity(300),
+            filesystem_history: BTreeMap::new(),
+            sample_interval: 1.0,
+            refresh_duration: Duration::ZERO,
             start_time: now,
         }
     }
 
     pub fn refresh(&mut self) {
-        self.system.refresh_processes_specifics(
-            ProcessesToUpdate::All,
-            true,
-            ProcessRefreshKind::new()
-                .with_cpu()
-                .with_memory(),
-        );
-        self.system.refresh_cpu_all();
-        self.system.refresh_memory();
+        let refresh_started = Instant::now();
+        self.source.refresh();
+        self.system_sample = self.source.system();
+        self.networks.refresh();
+        self.components.refresh();
+        self.battery = power::read_battery();
+        match self.disks_listed {
+            Some(listed) if listed.elapsed() >= DISK_LIST_INTERVAL => {
+                self.disks.refresh_list();
+                self.disks_listed = Some(Instant::now());
+            }
+            _ => self.disks.refresh(),
+        }
+        self.filesystems = self
+            .disks
+            .list()
+            .iter()
+            .map(|disk| {
+                let mount_point = disk.mount_point().to_string_lossy().to_string();
+                let file_system = disk.file_system().to_string_lossy().to_string();
+                FilesystemInfo {
+                    inodes: if is_network_filesystem(&file_system) {
+                        None
+                    } else {
+                        read_inodes(&mount_point)
+                    },
+                    mount_point,
+                    file_system,
+                    total_bytes: disk.total_space(),
+                    available_bytes: disk.available_space(),
+                }
+            })
+            .collect();
 
-        self.last_update = Instant::now();
+        let now = self.clock.now();
+        self.sample_interval = now.duration_since(self.last_update).as_secs_f64().max(0.001);
+        self.last_update = now;
 
         let elapsed = self.last_update.duration_since(self.start_time).as_secs_f64();
 
-        let total_cpu = self.system.cpus().iter().map(|cpu| cpu.cpu_usage()).sum::<f32>()
-            / self.system.cpus().len() as f32;
-        self.cpu_history.push((elapsed, total_cpu));
+        self.cpu_history.push((elapsed, self.system_sample.cpu_usage));
 
-        let used_memory = self.system.used_memory();
-        let used_gb = used_memory as f64 / 1_073_741_824.0;
+        let used_gb = self.system_sample.used_memory as f64 / 1_073_741_824.0;
         self.memory_history.push((elapsed, used_gb));
 
+        let disk_bytes: u64 = self
+            .source
+            .processes()
+            .iter()
+            .map(|process| process.disk_read_bytes + process.disk_written_bytes)
+            .sum();
+        let disk_mb_per_sec = disk_bytes as f64 / 1_048_576.0 / self.sample_interval;
+        self.disk_history.push((elapsed, disk_mb_per_sec));
+
+        self.process_tracker.update(
+            now,
+            elapsed,
+            self.source.processes().iter().map(|process| ProcessSample {
+                pid: process.pid,
+                name: &process.name,
+                parent: process.parent,
+                memory_mb: process.memory_bytes as f64 / 1_048_576.0,
+            }),
+        );
+
+        for (interface, data) in self.networks.list() {
+            let history = self
+                .network_history
+                .entry(interface.clone())
+                .or_insert_with(|| Vec::with_capacity(300));
+            history.push((
+                elapsed,
+                data.received() as f64 / self.sample_interval,
+                data.transmitted() as f64 / self.sample_interval,
+            ));
+            if history.len() > 300 {
+                history.remove(0);
+            }
+        }
+
+        if let Some(temperature) = self.cpu_temperature() {
+            self.temperature_history.push((elapsed, temperature));
+        }
+        if let Some(battery) = &self.battery {
+            self.battery_history.push((elapsed, battery.charge_percent));
+        }
+
+        for filesystem in &self.filesystems {
+            let history = self
+                .filesystem_history
+                .entry(filesystem.mount_point.clone())
+                .or_insert_with(|| Vec::with_capacity(300));
+            history.push((elapsed, filesystem.used_percent()));
+            if history.len() > 300 {
+                history.remove(0);
+            }
+        }
+
         if self.cpu_history.len() > 300 {
             self.cpu_history.remove(0);
         }
         if self.memory_history.len() > 300 {
             self.memory_history.remove(0);
         }
+        if self.disk_history.len() > 300 {
+            self.disk_history.remove(0);
+        }
+        if self.temperature_history.len() > 300 {
+            self.temperature_history.remove(0);
+        }
+        if self.battery_history.len() > 300 {
+            self.battery_history.remove(0);
+        }
+
+        // Interfaces and mounts that went away (VPNs, container veths) are
+        // dropped once their last sample has left the history window.
+        let window_start = self.cpu_history.first().map_or(elapsed, |(time, _)| *time);
+        self.network_history
+            .retain(|_, history| history.last().is_some_and(|(time, _, _)| *time >= window_start));
+        self.filesystem_history
+            .retain(|_, history| history.last().is_some_and(|(time, _)| *time >= window_start));
+
+        self.refresh_duration = refresh_started.elapsed();
+    }
+
+    pub fn refresh_duration(&self) -> Duration {
+        self.refresh_duration
     }
 
     pub fn get_processes(&self) -> Vec<ProcessInfo> {
         let mut processes: Vec<ProcessInfo> = self
-            .system
+            .source
             .processes()
             .iter()
-            .map(|(pid, process)| ProcessInfo {
-                pid: pid.as_u32(),
-                name: process.name().to_string_lossy().to_string(),
-                cpu_usage: process.cpu_usage(),
-                memory_mb: process.memory() as f64 / 1_048_576.0,
+            .map(|process| ProcessInfo {
+                pid: process.pid,
+                name: process.name.clone(),
+                parent: process.parent,
+                cpu_usage: process.cpu_usage,
+                memory_mb: process.memory_bytes as f64 / 1_048_576.0,
+                disk_read_bps: process.disk_read_bytes as f64 / self.sample_interval,
+                disk_write_bps: process.disk_written_bytes as f64 / self.sample_interval,
+                exe: process.exe.clone(),
+                uid: process.uid,
+                user: process.user.clone(),
+                session_id: process.session_id,
+                oom_score: process.oom_score,
+                oom_score_adj: process.oom_score_adj,
             })
             .collect();
 
@@ -93,7 +297,271 @@ impl MetricsCollector {
         &self.memory_history
     }
 
+    pub fn get_disk_history(&self) -> &[(f64, f64)] {
+        &self.disk_history
+    }
+
+    pub fn network_interfaces(&self) -> impl Iterator<Item = &str> {
+        self.network_history.keys().map(String::as_str)
+    }
+
+    pub fn get_network_history(&self, interface: &str) -> &[NetworkSample] {
+        self.network_history
+            .get(interface)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Hottest CPU sensor in °C. Falls back to every sensor when none is
+    /// labelled as a CPU/package/core sensor.
+    pub fn cpu_temperature(&self) -> Option<f32> {
+        let is_cpu = |label: &str| {
+            let label = label.to_lowercase();
+            ["cpu", "package", "core", "tctl", "tdie"]
+                .iter()
+                .any(|key| label.contains(key))
+        };
+        let readings = |cpu_only: bool| {
+            self.components
+                .list()
+                .iter()
+                .filter(move |c| !cpu_only || is_cpu(c.label()))
+                .map(|c| c.temperature())
+                .filter(|t| t.is_finite() && *t > 0.0)
+                .reduce(f32::max)
+        };
+        readings(true).or_else(|| readings(false))
+    }
+
+    pub fn components(&self) -> &Components {
+        &self.components
+    }
+
+    pub fn battery(&self) -> Option<&BatteryStatus> {
+        self.battery.as_ref()
+    }
+
+    pub fn get_temperature_history(&self) -> &[(f64, f32)] {
+        &self.temperature_history
+    }
+
+    pub fn get_battery_history(&self) -> &[(f64, f32)] {
+        &self.battery_history
+    }
+
+    pub fn filesystems(&self) -> &[FilesystemInfo] {
+        &self.filesystems
+    }
+
+    pub fn get_filesystem_history(&self, mount_point: &str) -> &[(f64, f32)] {
+        self.filesystem_history
+            .get(mount_point)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Swaps the process backend, keeping all history.
+    pub fn set_source(&mut self, kind: ProcessSourceKind) {
+        self.source = source::create(kind);
+        self.system_sample = self.source.system();
+    }
+
+    pub fn sampling_cost(&self) -> SamplingCost {
+        self.source.cost()
+    }
+
+    pub fn invalidate_oom_scores(&mut self, pid: u32) {
+        self.source.invalidate_oom_scores(pid);
+    }
+
+    pub fn process_tracker(&self) -> &ProcessTracker {
+        &self.process_tracker
+    }
+
+    pub fn cpu_count(&self) -> usize {
+        self.system_sample.cpu_count
+    }
+
+    pub fn get_available_memory_mb(&self) -> f64 {
+        self.system_sample.available_memory as f64 / 1_048_576.0
+    }
+
     pub fn get_total_memory_gb(&self) -> f64 {
-        self.system.total_memory() as f64 / 1_073_741_824.0
+        self.system_sample.total_memory as f64 / 1_073_741_824.0
+    }
+}
+
+/// Filesystems whose server may not answer, where `statvfs` can block for
+/// as long as it is unreachable. sysinfo already leaves out NFS and CIFS.
+fn is_network_filesystem(file_system: &str) -> bool {
+    matches!(
+        file_system,
+        "nfs" | "nfs4" | "cifs" | "smb3" | "smbfs" | "ceph" | "glusterfs" | "9p" | "afs" | "lustre"
+    ) || file_system.starts_with("fuse.sshfs")
+}
+
+#[cfg(target_os = "linux")]
+fn read_inodes(mount_point: &str) -> Option<(u64, u64)> {
+    let path = std::ffi::CString::new(mount_point).ok()?;
+    unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(path.as_ptr(), &mut stat) != 0 {
+            return None;
+        }
+        Some((stat.f_files as u64, stat.f_ffree as u64))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_inodes(_mount_point: &str) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::fake::{ManualClock, ScriptedSource};
+    use crate::source::SourceProcess;
+    use std::time::Duration;
+
+    const MB: u64 = 1_048_576;
+
+    fn system(cpu_usage: f32, used_memory: u64) -> SystemSample {
+        SystemSample {
+            cpu_usage,
+            cpu_count: 4,
+            total_memory: 8 * 1024 * MB,
+            used_memory,
+            available_memory: 8 * 1024 * MB - used_memory,
+        }
+    }
+
+    fn process(pid: u32, name: &str, memory_mb: u64) -> SourceProcess {
+        SourceProcess {
+            pid,
+            name: name.to_string(),
+            memory_bytes: memory_mb * MB,
+            ..Default::default()
+        }
+    }
+
+    fn collector(script: Vec<(SystemSample, Vec<SourceProcess>)>) -> (MetricsCollector, ManualClock) {
+        let clock = ManualClock::new();
+        let collector = MetricsCollector::with_source(
+            Box::new(ScriptedSource::new(script)),
+            Box::new(clock.clone()),
+        );
+        (collector, clock)
+    }
+
+    #[test]
+    fn refresh_records_samples_at_clock_time() {
+        let (mut metrics, clock) = collector(vec![
+            (system(0.0, 0), Vec::new()),
+            (system(25.0, 2048 * MB), Vec::new()),
+            (system(50.0, 3072 * MB), Vec::new()),
+        ]);
+
+        clock.advance(Duration::from_secs(1));
+        metrics.refresh();
+        clock.advance(Duration::from_millis(500));
+        metrics.refresh();
+
+        assert_eq!(metrics.get_cpu_history(), &[(1.0, 25.0), (1.5, 50.0)]);
+        assert_eq!(metrics.get_memory_history(), &[(1.0, 2.0), (1.5, 3.0)]);
+        assert_eq!(metrics.cpu_count(), 4);
+        assert_eq!(metrics.get_available_memory_mb(), 5120.0);
+    }
+
+    #[test]
+    fn disk_rates_use_the_sample_interval() {
+        let mut busy = process(10, "dd", 1);
+        busy.disk_read_bytes = 4 * MB;
+        busy.disk_written_bytes = MB;
+        let (mut metrics, clock) = collector(vec![
+            (system(0.0, 0), Vec::new()),
+            (system(0.0, 0), vec![busy]),
+        ]);
+
+        clock.advance(Duration::from_secs(2));
+        metrics.refresh();
+
+        let processes = metrics.get_processes();
+        assert_eq!(processes[0].disk_read_bps, 2.0 * MB as f64);
+        assert_eq!(processes[0].disk_write_bps, 0.5 * MB as f64);
+        assert_eq!(metrics.get_disk_history(), &[(2.0, 2.5)]);
+    }
+
+    #[test]
+    fn histories_are_capped_at_300_samples() {
+        let (mut metrics, clock) = collector(vec![(system(10.0, MB), Vec::new())]);
+
+        for _ in 0..350 {
+            clock.advance(Duration::from_secs(1));
+            metrics.refresh();
+        }
+
+        assert_eq!(metrics.get_cpu_history().len(), 300);
+        assert_eq!(metrics.get_memory_history().len(), 300);
+        assert_eq!(metrics.get_disk_history().len(), 300);
+        assert_eq!(metrics.process_tracker().get_churn_history().len(), 300);
+        // The oldest samples are dropped first.
+        assert_eq!(metrics.get_cpu_history()[0].0, 51.0);
+        assert_eq!(metrics.get_cpu_history()[299].0, 350.0);
+    }
+
+    #[test]
+    fn vanished_interfaces_leave_after_the_window() {
+        let (mut metrics, clock) = collector(vec![(system(10.0, MB), Vec::new())]);
+        clock.advance(Duration::from_secs(1));
+        metrics.refresh();
+        metrics.network_history.insert("veth0".to_string(), vec![(1.0, 0.0, 0.0)]);
+
+        for _ in 0..299 {
+            clock.advance(Duration::from_secs(1));
+            metrics.refresh();
+        }
+        assert!(metrics.network_interfaces().any(|name| name == "veth0"));
+
+        clock.advance(Duration::from_secs(1));
+        metrics.refresh();
+        assert_eq!(metrics.network_interfaces().count(), 0);
+    }
+
+    #[test]
+    fn get_processes_sorts_by_memory_descending() {
+        let (mut metrics, clock) = collector(vec![(
+            system(0.0, 0),
+            vec![
+                process(1, "init", 10),
+                process(2, "browser", 900),
+                process(3, "editor", 300),
+                process(4, "shell", 10),
+            ],
+        )]);
+
+        clock.advance(Duration::from_secs(1));
+        metrics.refresh();
+
+        let order: Vec<u32> = metrics.get_processes().iter().map(|p| p.pid).collect();
+        assert_eq!(order[..2], [2, 3]);
+        assert_eq!(order.len(), 4);
+        assert!(metrics.get_processes()[2..].iter().all(|p| p.memory_mb == 10.0));
+    }
+
+    #[test]
+    fn get_processes_follows_the_latest_sample() {
+        let (mut metrics, clock) = collector(vec![
+            (system(0.0, 0), vec![process(1, "a", 100), process(2, "b", 50)]),
+            (system(0.0, 0), vec![process(2, "b", 500), process(3, "c", 200)]),
+        ]);
+
+        clock.advance(Duration::from_secs(1));
+        metrics.refresh();
+
+        let processes = metrics.get_processes();
+        let names: Vec<&str> = processes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["b", "c"]);
+        assert_eq!(processes[0].memory_mb, 500.0);
     }
 }
@@ -0,0 +1,426 @@
+use crate::audit::{self, AuditEntry, KillOrigin};
+use crate::killer::{send_sigterm, stop_process, KillContext, KillStatus};
+use crate::metrics::ProcessInfo;
+use crate::protection::ProtectionRules;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum VictimPolicy {
+    LargestRss,
+    FastestGrowth,
+    Allowlist,
+}
+
+impl VictimPolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            VictimPolicy::LargestRss => "Largest memory",
+            VictimPolicy::FastestGrowth => "Fastest growth",
+            VictimPolicy::Allowlist => "Largest on allowlist",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GuardAction {
+    Terminate,
+    Stop,
+}
+
+impl GuardAction {
+    pub fn signal_name(self) -> &'static str {
+        match self {
+            GuardAction::Terminate => "SIGTERM",
+            GuardAction::Stop => "SIGSTOP",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OomGuardSettings {
+    pub enabled: bool,
+    pub dry_run: bool,
+    pub min_available_mb: u64,
+    /// Linux only: PSI `full avg10` percentage that counts as pressure.
+    pub psi_full_limit: f32,
+    pub sustain_seconds: u64,
+    pub cooldown_seconds: u64,
+    pub victim_policy: VictimPolicy,
+    pub allowlist: Vec<String>,
+    pub action: GuardAction,
+}
+
+impl Default for OomGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dry_run: true,
+            min_available_mb: 512,
+            psi_full_limit: 20.0,
+            sustain_seconds: 5,
+            cooldown_seconds: 30,
+            victim_policy: VictimPolicy::LargestRss,
+            allowlist: Vec::new(),
+            action: GuardAction::Terminate,
+        }
+    }
+}
+
+/// What the guard did (or would have done, in dry-run mode) on one tick.
+pub struct GuardEvent {
+    pub pid: u32,
+    pub name: String,
+    pub memory_mb: f64,
+    pub trigger: String,
+    pub action: GuardAction,
+    /// `None` in dry-run mode, where no signal is sent.
+    pub status: Option<KillStatus>,
+}
+
+pub struct OomGuard {
+    pressure_since: Option<Instant>,
+    last_action: Option<Instant>,
+    previous_sample: Option<(Instant, HashMap<u32, f64>)>,
+}
+
+impl OomGuard {
+    pub fn new() -> Self {
+        Self {
+            pressure_since: None,
+            last_action: None,
+            previous_sample: None,
+        }
+    }
+
+    /// Called after every metrics refresh. Acts once pressure has held for
+    /// `sustain_seconds`, then waits out `cooldown_seconds` before acting again.
+    pub fn evaluate(
+        &mut self,
+        settings: &OomGuardSettings,
+        protection: &ProtectionRules,
+        available_mb: f64,
+        processes: &[ProcessInfo],
+        now: Instant,
+    ) -> Option<GuardEvent> {
+        let growth = self.growth_rates(now, processes);
+
+        if !settings.enabled {
+            self.pressure_since = None;
+            return None;
+        }
+
+        let Some(trigger) = pressure_trigger(settings, available_mb) else {
+            self.pressure_since = None;
+            return None;
+        };
+
+        let since = *self.pressure_since.get_or_insert(now);
+        if now.duration_since(since) < Duration::from_secs(settings.sustain_seconds) {
+            return None;
+        }
+        if self
+            .last_action
+            .is_some_and(|last| now.duration_since(last) < Duration::from_secs(settings.cooldown_seconds))
+        {
+            return None;
+        }
+
+        let victim = pick_victim(settings, protection, processes, &growth)?;
+        self.last_action = Some(now);
+        self.pressure_since = None;
+
+        let status = if settings.dry_run {
+            let _ = audit::record(&AuditEntry {
+                timestamp: audit::unix_now(),
+                pid: victim.pid,
+                name: victim.name.clone(),
+                command_line: String::new(),
+                user: victim
+                    .user
+                    .clone()
+                    .or_else(|| victim.uid.map(|uid| uid.to_string()))
+                    .unwrap_or_default(),
+                signal: settings.action.signal_name().to_string(),
+                status: format!("Dry run, not sent ({})", trigger),
+                origin: KillOrigin::Rule,
+            });
+            None
+        } else {
+            let ctx = KillContext {
+                guard: Some(protection),
+                escalation: &Default::default(),
+                origin: KillOrigin::Rule,
+            };
+            Some(match settings.action {
+                GuardAction::Terminate => send_sigterm(victim.pid, &ctx),
+                GuardAction::Stop => stop_process(victim.pid, &ctx),
+            })
+        };
+
+        Some(GuardEvent {
+            pid: victim.pid,
+            name: victim.name.clone(),
+            memory_mb: victim.memory_mb,
+            trigger,
+            action: settings.action,
+            status,
+        })
+    }
+
+    /// Memory growth in MB/s per PID since the previous call.
+    fn growth_rates(&mut self, now: Instant, processes: &[ProcessInfo]) -> HashMap<u32, f64> {
+        let current: HashMap<u32, f64> = processes.iter().map(|p| (p.pid, p.memory_mb)).collect();
+
+        let mut growth = HashMap::new();
+        if let Some((then, previous)) = &self.previous_sample {
+            let elapsed = now.duration_since(*then).as_secs_f64();
+            if elapsed > 0.0 {
+                for (pid, memory) in &current {
+                    if let Some(before) = previous.get(pid) {
+                        growth.insert(*pid, (memory - before) / elapsed);
+                    }
+                }
+            }
+        }
+
+        self.previous_sample = Some((now, current));
+        growth
+    }
+}
+
+/// Describes why memory is under pressure, or `None` if it is not.
+fn pressure_trigger(settings: &OomGuardSettings, available_mb: f64) -> Option<String> {
+    if available_mb < settings.min_available_mb as f64 {
+        return Some(format!(
+            "available memory {:.0} MB below {} MB",
+            available_mb, settings.min_available_mb
+        ));
+    }
+    if let Some(full) = read_psi_full_avg10() {
+        if full > settings.psi_full_limit {
+            return Some(format!(
+                "PSI full {:.1}% above {:.1}%",
+                full, settings.psi_full_limit
+            ));
+        }
+    }
+    None
+}
+
+fn pick_victim<'a>(
+    settings: &OomGuardSettings,
+    protection: &ProtectionRules,
+    processes: &'a [ProcessInfo],
+    growth: &HashMap<u32, f64>,
+) -> Option<&'a ProcessInfo> {
+    let candidates = processes.iter().filter(|p| {
+        protection
+            .reason(p.pid, &p.name, p.exe.as_deref(), p.uid)
+            .is_none()
+    });
+
+    let by_memory = |a: &&ProcessInfo, b: &&ProcessInfo| {
+        a.memory_mb
+            .partial_cmp(&b.memory_mb)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    };
+
+    match settings.victim_policy {
+        VictimPolicy::LargestRss => candidates.max_by(by_memory),
+        VictimPolicy::FastestGrowth => candidates
+            .filter(|p| growth.get(&p.pid).is_some_and(|rate| *rate > 0.0))
+            .max_by(|a, b| {
+                growth[&a.pid]
+                    .partial_cmp(&growth[&b.pid])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }),
+        VictimPolicy::Allowlist => candidates
+            .filter(|p| settings.allowlist.iter().any(|name| name == &p.name))
+            .max_by(by_memory),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_psi_full_avg10() -> Option<f32> {
+    let contents = std::fs::read_to_string("/proc/pressure/memory").ok()?;
+    let line = contents.lines().find(|line| line.starts_with("full"))?;
+    line.split_whitespace()
+        .find_map(|field| field.strip_prefix("avg10="))
+        .and_then(|value| value.parse().ok())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_psi_full_avg10() -> Option<f32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn settings(victim_policy: VictimPolicy) -> OomGuardSettings {
+        OomGuardSettings {
+            enabled: true,
+            dry_run: true,
+            min_available_mb: 512,
+            // Keep the host's real PSI readings out of these tests.
+            psi_full_limit: 100.0,
+            sustain_seconds: 0,
+            cooldown_seconds: 0,
+            victim_policy,
+            allowlist: Vec::new(),
+            action: GuardAction::Terminate,
+        }
+    }
+
+    fn unprotected() -> ProtectionRules {
+        ProtectionRules {
+            names: Vec::new(),
+            paths: Vec::new(),
+            pids: Vec::new(),
+            uids: Vec::new(),
+        }
+    }
+
+    fn process(pid: u32, name: &str, memory_mb: f64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            parent: None,
+            cpu_usage: 0.0,
+            memory_mb,
+            disk_read_bps: 0.0,
+            disk_write_bps: 0.0,
+            exe: None,
+            uid: None,
+            user: None,
+            session_id: None,
+            oom_score: None,
+            oom_score_adj: None,
+        }
+    }
+
+    fn victim(
+        guard: &mut OomGuard,
+        settings: &OomGuardSettings,
+        processes: &[ProcessInfo],
+        now: Instant,
+    ) -> Option<u32> {
+        guard
+            .evaluate(settings, &unprotected(), 100.0, processes, now)
+            .map(|event| event.pid)
+    }
+
+    #[test]
+    fn acts_only_after_pressure_is_sustained() {
+        let settings = OomGuardSettings {
+            sustain_seconds: 5,
+            ..settings(VictimPolicy::LargestRss)
+        };
+        let processes = [process(100, "hog", 900.0)];
+        let mut guard = OomGuard::new();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        assert_eq!(victim(&mut guard, &settings, &processes, t0), None);
+        assert_eq!(victim(&mut guard, &settings, &processes, at(4)), None);
+        // Relief in between restarts the window.
+        assert!(guard.evaluate(&settings, &unprotected(), 4096.0, &processes, at(5)).is_none());
+        assert_eq!(victim(&mut guard, &settings, &processes, at(6)), None);
+        assert_eq!(victim(&mut guard, &settings, &processes, at(10)), None);
+        assert_eq!(victim(&mut guard, &settings, &processes, at(11)), Some(100));
+    }
+
+    #[test]
+    fn waits_out_the_cooldown() {
+        let settings = OomGuardSettings {
+            cooldown_seconds: 30,
+            ..settings(VictimPolicy::LargestRss)
+        };
+        let processes = [process(100, "hog", 900.0)];
+        let mut guard = OomGuard::new();
+        let t0 = Instant::now();
+
+        assert_eq!(victim(&mut guard, &settings, &processes, t0), Some(100));
+        assert_eq!(victim(&mut guard, &settings, &processes, t0 + Duration::from_secs(29)), None);
+        assert_eq!(victim(&mut guard, &settings, &processes, t0 + Duration::from_secs(30)), Some(100));
+    }
+
+    #[test]
+    fn largest_rss_picks_the_biggest_process() {
+        let processes = [process(100, "a", 200.0), process(101, "b", 800.0), process(102, "c", 400.0)];
+        let mut guard = OomGuard::new();
+        let picked = victim(&mut guard, &settings(VictimPolicy::LargestRss), &processes, Instant::now());
+        assert_eq!(picked, Some(101));
+    }
+
+    #[test]
+    fn fastest_growth_picks_the_fastest_grower() {
+        let settings = settings(VictimPolicy::FastestGrowth);
+        let mut guard = OomGuard::new();
+        let t0 = Instant::now();
+        let before = [process(100, "big", 800.0), process(101, "leak", 100.0)];
+        // No earlier sample means no rates, so nothing is picked yet.
+        assert_eq!(victim(&mut guard, &settings, &before, t0), None);
+
+        let after = [process(100, "big", 810.0), process(101, "leak", 300.0)];
+        assert_eq!(victim(&mut guard, &settings, &after, t0 + Duration::from_secs(1)), Some(101));
+    }
+
+    #[test]
+    fn allowlist_picks_the_largest_listed_process() {
+        let settings = OomGuardSettings {
+            allowlist: vec!["chrome".to_string(), "node".to_string()],
+            ..settings(VictimPolicy::Allowlist)
+        };
+        let processes = [process(100, "postgres", 900.0), process(101, "node", 300.0), process(102, "chrome", 500.0)];
+        let mut guard = OomGuard::new();
+        assert_eq!(victim(&mut guard, &settings, &processes, Instant::now()), Some(102));
+
+        let unlisted = [process(100, "postgres", 900.0)];
+        assert_eq!(victim(&mut OomGuard::new(), &settings, &unlisted, Instant::now()), None);
+    }
+
+    #[test]
+    fn skips_protected_processes() {
+        let protection = ProtectionRules {
+            names: vec!["postgres".to_string()],
+            ..unprotected()
+        };
+        let processes = [process(100, "postgres", 900.0), process(101, "cache", 300.0)];
+        let event = OomGuard::new().evaluate(
+            &settings(VictimPolicy::LargestRss),
+            &protection,
+            100.0,
+            &processes,
+            Instant::now(),
+        );
+        assert_eq!(event.map(|event| event.pid), Some(101));
+    }
+
+    #[test]
+    fn dry_run_only_writes_the_audit_log() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let processes = [process(child.id(), "sleep", 900.0)];
+        let event = OomGuard::new()
+            .evaluate(&settings(VictimPolicy::LargestRss), &unprotected(), 100.0, &processes, Instant::now())
+            .unwrap();
+
+        assert_eq!(event.pid, child.id());
+        assert_eq!(event.status, None);
+        assert_eq!(child.try_wait().unwrap(), None);
+        let logged = audit::load()
+            .into_iter()
+            .find(|entry| entry.pid == child.id())
+            .unwrap();
+        assert!(logged.status.starts_with("Dry run"));
+        assert_eq!(logged.origin, KillOrigin::Rule);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
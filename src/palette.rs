@@ -0,0 +1,137 @@
+// Command palette: one search box over the action registry and the process
+// list. Matching is a case-insensitive subsequence search that favours
+// consecutive characters and word starts, so "scpu" finds "Sort by CPU" and
+// "ffx" finds "firefox".
+
+use crate::actions::Action;
+use crate::metrics::ProcessInfo;
+
+/// Most results shown at once.
+const MAX_RESULTS: usize = 50;
+
+#[derive(Default)]
+pub struct CommandPalette {
+    pub query: String,
+    /// Index into the current results.
+    pub selected: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteItem {
+    Action(Action),
+    Process { pid: u32, label: String },
+}
+
+/// Scores `text` against `query`, or `None` if the query's characters do
+/// not all appear in order. Whitespace in the query is ignored.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = position + text[position..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if previous.is_none() {
+            // Matches that start late in the text rank lower.
+            score -= index.min(10) as i32;
+        }
+        previous = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+/// Actions and processes matching `query`, best first. An empty query lists
+/// the actions in registry order.
+pub fn search(query: &str, actions: &[Action], processes: &[ProcessInfo]) -> Vec<PaletteItem> {
+    let query = query.trim();
+    if query.is_empty() {
+        return actions.iter().cloned().map(PaletteItem::Action).collect();
+    }
+
+    let mut scored: Vec<(i32, PaletteItem)> = actions
+        .iter()
+        .filter_map(|action| Some((fuzzy_score(query, &action.label())?, PaletteItem::Action(action.clone()))))
+        .collect();
+
+    let pid_query = query.chars().all(|c| c.is_ascii_digit());
+    scored.extend(processes.iter().filter_map(|process| {
+        let score = if pid_query && process.pid.to_string().starts_with(query) {
+            // Exact PIDs first, then the shortest completions.
+            100 - (process.pid.to_string().len() - query.len()) as i32
+        } else {
+            fuzzy_score(query, &process.name)?
+        };
+        let label = format!("{} ({}), {:.1} MB", process.name, process.pid, process.memory_mb);
+        Some((score, PaletteItem::Process { pid: process.pid, label }))
+    }));
+
+    // Stable, so equal scores keep registry and process list order.
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().take(MAX_RESULTS).map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::SortMode;
+
+    fn process(pid: u32, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            parent: None,
+            cpu_usage: 0.0,
+            memory_mb: 0.0,
+            disk_read_bps: 0.0,
+            disk_write_bps: 0.0,
+            exe: None,
+            uid: None,
+            user: None,
+            session_id: None,
+            oom_score: None,
+            oom_score_adj: None,
+        }
+    }
+
+    #[test]
+    fn fuzzy_matches_subsequences_only() {
+        assert!(fuzzy_score("ffx", "firefox").is_some());
+        assert!(fuzzy_score("sort cpu", "Sort by CPU").is_some());
+        assert!(fuzzy_score("FIRE", "firefox").is_some());
+        assert_eq!(fuzzy_score("xff", "firefox"), None);
+        assert_eq!(fuzzy_score("chrome", "chrom"), None);
+    }
+
+    #[test]
+    fn prefers_word_starts_and_runs() {
+        let tree = fuzzy_score("tree", "Toggle tree view").unwrap();
+        let scattered = fuzzy_score("tree", "Terminate selected").unwrap();
+        assert!(tree > scattered);
+        assert!(fuzzy_score("code", "code").unwrap() > fuzzy_score("code", "vscode-helper").unwrap());
+    }
+
+    #[test]
+    fn searches_actions_and_processes_together() {
+        let actions = [Action::Sort(SortMode::Memory), Action::Sort(SortMode::Cpu)];
+        let processes = [process(4242, "cpuburn"), process(42, "sshd"), process(7, "postgres")];
+
+        let results = search("cpu", &actions, &processes);
+        assert!(results.contains(&PaletteItem::Action(Action::Sort(SortMode::Cpu))));
+        assert!(results.iter().any(|item| matches!(item, PaletteItem::Process { pid: 4242, .. })));
+        assert!(!results.contains(&PaletteItem::Action(Action::Sort(SortMode::Memory))));
+
+        let results = search("42", &actions, &processes);
+        assert!(matches!(results[..], [PaletteItem::Process { pid: 42, .. }, PaletteItem::Process { pid: 4242, .. }]));
+
+        assert_eq!(search("  ", &actions, &processes).len(), actions.len());
+    }
+}
@@ -0,0 +1,311 @@
+// System-wide hotkey under Wayland through the XDG desktop portal
+// (org.freedesktop.portal.GlobalShortcuts). The desktop decides the final key
+// and may ask the user to confirm it, so registration runs on its own thread
+// and reports back through `status`.
+
+use crate::hotkey::HotkeyTrigger;
+use crate::keybindings::KeyBinding;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use zbus::blocking::{proxy, Connection, Proxy};
+use zbus::message::Message;
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+const PORTAL: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const SHORTCUT_ID: &str = "toggle-window";
+
+type Results = HashMap<String, OwnedValue>;
+
+pub struct PortalHotkey {
+    status: Arc<Mutex<Result<String, String>>>,
+    stop: Arc<AtomicBool>,
+    connection: Arc<Mutex<Option<Connection>>>,
+    session: Arc<Mutex<Option<OwnedObjectPath>>>,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl PortalHotkey {
+    pub fn register(binding: KeyBinding, trigger: HotkeyTrigger) -> Self {
+        let mut hotkey = Self {
+            status: Arc::new(Mutex::new(Err("waiting for the desktop portal".to_string()))),
+            stop: Arc::new(AtomicBool::new(false)),
+            connection: Arc::new(Mutex::new(None)),
+            session: Arc::new(Mutex::new(None)),
+            listener: None,
+        };
+
+        let status = hotkey.status.clone();
+        let stop = hotkey.stop.clone();
+        let connection = hotkey.connection.clone();
+        let session = hotkey.session.clone();
+        hotkey.listener = Some(thread::spawn(move || {
+            if let Err(e) = run(binding, &trigger, &status, &stop, &connection, &session) {
+                *status.lock().unwrap() = Err(e);
+            }
+        }));
+        hotkey
+    }
+
+    /// The trigger as the desktop describes it once bound, or why it is not.
+    pub fn status(&self) -> Result<String, String> {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+impl Drop for PortalHotkey {
+    /// Closes the session so the desktop releases the shortcut, then the
+    /// connection, which ends the listener thread's signal stream.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let Some(conn) = self.connection.lock().unwrap().take() else {
+            // Still connecting; the thread sees `stop` once it has.
+            return;
+        };
+        if let Some(handle) = self.session.lock().unwrap().take() {
+            let _ = conn.call_method(Some(PORTAL), handle.as_str(), Some("org.freedesktop.portal.Session"), "Close", &());
+        }
+        let _ = conn.close();
+        if let Some(listener) = self.listener.take() {
+            let _ = listener.join();
+        }
+    }
+}
+
+fn run(
+    binding: KeyBinding,
+    trigger: &HotkeyTrigger,
+    status: &Mutex<Result<String, String>>,
+    stop: &AtomicBool,
+    connection: &Mutex<Option<Connection>>,
+    session: &Mutex<Option<OwnedObjectPath>>,
+) -> Result<(), String> {
+    let conn = Connection::session().map_err(|e| format!("no D-Bus session bus ({})", e))?;
+    {
+        let mut connection = connection.lock().unwrap();
+        if stop.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        *connection = Some(conn.clone());
+    }
+    let portal = Proxy::new(&conn, PORTAL, PORTAL_PATH, "org.freedesktop.portal.GlobalShortcuts")
+        .map_err(|e| e.to_string())?;
+    let activated = portal
+        .receive_signal("Activated")
+        .map_err(|e| format!("GlobalShortcuts portal not available ({})", e))?;
+
+    let options = HashMap::from([
+        ("handle_token", Value::from("memwatch_create")),
+        ("session_handle_token", Value::from(format!("memwatch{}", std::process::id()))),
+    ]);
+    let results = request(&conn, "memwatch_create", || portal.call_method("CreateSession", &(options,)))?;
+    let handle = match results.get("session_handle").map(|value| &**value) {
+        Some(Value::Str(path)) => OwnedObjectPath::try_from(path.as_str()).map_err(|e| e.to_string())?,
+        Some(Value::ObjectPath(path)) => OwnedObjectPath::from(path.to_owned()),
+        _ => return Err("portal did not return a session".to_string()),
+    };
+    *session.lock().unwrap() = Some(handle.clone());
+
+    let shortcut = HashMap::from([
+        ("description", Value::from("Show or hide memwatch")),
+        ("preferred_trigger", Value::from(preferred_trigger(&binding))),
+    ]);
+    let options = HashMap::from([("handle_token", Value::from("memwatch_bind"))]);
+    let results = request(&conn, "memwatch_bind", || {
+        portal.call_method("BindShortcuts", &(&handle, vec![(SHORTCUT_ID, shortcut)], "", options))
+    })?;
+    *status.lock().unwrap() = Ok(trigger_description(&results).unwrap_or_else(|| binding.to_string()));
+
+    for message in activated {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let body = message.body();
+        let Ok((session_handle, id, _, _)) = body.deserialize::<(OwnedObjectPath, String, u64, Results)>() else {
+            continue;
+        };
+        if session_handle == handle && id == SHORTCUT_ID {
+            trigger.fire();
+        }
+    }
+    Ok(())
+}
+
+/// Makes a portal call and waits for its `Request.Response`. The request
+/// path is derived from the handle token, so the response cannot be missed.
+fn request(
+    conn: &Connection,
+    token: &str,
+    call: impl FnOnce() -> zbus::Result<Message>,
+) -> Result<Results, String> {
+    let sender = conn
+        .unique_name()
+        .ok_or("not connected to the session bus")?
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let path = format!("{}/request/{}/{}", PORTAL_PATH, sender, token);
+    let request: Proxy = proxy::Builder::new(conn)
+        .destination(PORTAL)
+        .and_then(|builder| builder.path(path))
+        .and_then(|builder| builder.interface("org.freedesktop.portal.Request"))
+        .map(|builder| builder.cache_properties(CacheProperties::No))
+        .and_then(|builder| builder.build())
+        .map_err(|e| e.to_string())?;
+    let mut responses = request.receive_signal("Response").map_err(|e| e.to_string())?;
+
+    call().map_err(|e| e.to_string())?;
+    let message = responses.next().ok_or("portal closed the request")?;
+    let (code, results): (u32, Results) = message.body().deserialize().map_err(|e| e.to_string())?;
+    match code {
+        0 => Ok(results),
+        1 => Err("cancelled by the user".to_string()),
+        _ => Err("refused by the desktop".to_string()),
+    }
+}
+
+/// Formats a binding the way the shortcuts specification expects, e.g.
+/// "CTRL+ALT+m".
+fn preferred_trigger(binding: &KeyBinding) -> String {
+    let modifiers = binding.modifiers;
+    let mut parts = Vec::new();
+    if modifiers.ctrl || modifiers.command {
+        parts.push("CTRL".to_string());
+    }
+    if modifiers.alt {
+        parts.push("ALT".to_string());
+    }
+    if modifiers.shift {
+        parts.push("SHIFT".to_string());
+    }
+    parts.push(match crate::x11_hotkey::keysym(binding.key) {
+        Some((_, name)) => name,
+        None => binding.key.name().to_string(),
+    });
+    parts.join("+")
+}
+
+fn trigger_description(results: &Results) -> Option<String> {
+    let Value::Array(shortcuts) = &**results.get("shortcuts")? else {
+        return None;
+    };
+    shortcuts.iter().find_map(|shortcut| {
+        let Value::Structure(shortcut) = shortcut else {
+            return None;
+        };
+        let [Value::Str(id), Value::Dict(properties)] = shortcut.fields() else {
+            return None;
+        };
+        if id.as_str() != SHORTCUT_ID {
+            return None;
+        }
+        properties.get::<&str, String>(&"trigger_description").ok().flatten()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui;
+    use std::time::{Duration, Instant};
+    use zbus::message::Header;
+    use zbus::zvariant::ObjectPath;
+
+    /// Answers every request immediately, like a desktop that accepts the
+    /// shortcut without asking.
+    struct FakePortal;
+
+    impl FakePortal {
+        async fn respond(conn: &zbus::Connection, header: &Header<'_>, options: &Results, results: HashMap<&str, Value<'_>>) -> OwnedObjectPath {
+            let sender = header.sender().unwrap().trim_start_matches(':').replace('.', "_");
+            let token: String = options["handle_token"].try_clone().unwrap().try_into().unwrap();
+            let path = OwnedObjectPath::try_from(format!("{}/request/{}/{}", PORTAL_PATH, sender, token)).unwrap();
+            conn.emit_signal(None::<()>, &path, "org.freedesktop.portal.Request", "Response", &(0u32, results))
+                .await
+                .unwrap();
+            path
+        }
+    }
+
+    #[zbus::interface(name = "org.freedesktop.portal.GlobalShortcuts")]
+    impl FakePortal {
+        async fn create_session(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] conn: &zbus::Connection,
+            options: Results,
+        ) -> OwnedObjectPath {
+            let session = Value::from("/org/freedesktop/portal/desktop/session/1_1/memwatch");
+            Self::respond(conn, &header, &options, HashMap::from([("session_handle", session)])).await
+        }
+
+        async fn bind_shortcuts(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] conn: &zbus::Connection,
+            _session_handle: ObjectPath<'_>,
+            _shortcuts: Vec<(String, Results)>,
+            _parent_window: String,
+            options: Results,
+        ) -> OwnedObjectPath {
+            let properties = HashMap::from([("trigger_description", Value::from("Ctrl+Alt+M"))]);
+            let shortcuts = Value::from(vec![(SHORTCUT_ID, properties)]);
+            Self::respond(conn, &header, &options, HashMap::from([("shortcuts", shortcuts)])).await
+        }
+    }
+
+    fn wait_for(mut condition: impl FnMut() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[test]
+    #[ignore = "needs a private session bus: dbus-run-session -- cargo test portal -- --ignored"]
+    fn binds_and_fires_through_the_portal() {
+        let portal = zbus::blocking::connection::Builder::session()
+            .unwrap()
+            .name(PORTAL)
+            .unwrap()
+            .serve_at(PORTAL_PATH, FakePortal)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let trigger = HotkeyTrigger::new(&egui::Context::default());
+        let hotkey = PortalHotkey::register(KeyBinding::parse("Alt+Cmd+M").unwrap(), trigger.clone());
+        assert!(wait_for(|| hotkey.status().is_ok()), "{:?}", hotkey.status());
+        assert_eq!(hotkey.status(), Ok("Ctrl+Alt+M".to_string()));
+
+        let session = ObjectPath::try_from("/org/freedesktop/portal/desktop/session/1_1/memwatch").unwrap();
+        let activated = (session, SHORTCUT_ID, 0u64, HashMap::<&str, Value>::new());
+        portal
+            .emit_signal(None::<()>, PORTAL_PATH, "org.freedesktop.portal.GlobalShortcuts", "Activated", &activated)
+            .unwrap();
+        assert!(wait_for(|| trigger.take()));
+
+        // Dropping joins the listener, so this returns only once the thread
+        // and its connection are gone.
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            drop(hotkey);
+            let _ = done_tx.send(());
+        });
+        assert!(done_rx.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+
+    #[test]
+    fn formats_preferred_trigger() {
+        let binding = KeyBinding::parse("Alt+Cmd+M").unwrap();
+        assert_eq!(preferred_trigger(&binding), "CTRL+ALT+m");
+        let binding = KeyBinding::parse("Shift+F5").unwrap();
+        assert_eq!(preferred_trigger(&binding), "SHIFT+F5");
+    }
+}
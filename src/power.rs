@@ -0,0 +1,66 @@
+// Battery and AC adapter state. Linux reads /sys/class/power_supply; other
+// platforms report no battery.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatteryStatus {
+    pub charge_percent: f32,
+    /// "Charging", "Discharging", "Full", ... as reported by the kernel.
+    pub state: String,
+    /// Positive while discharging or charging; `None` if the driver does not
+    /// expose power or current.
+    pub power_watts: Option<f32>,
+    pub ac_online: Option<bool>,
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_battery() -> Option<BatteryStatus> {
+    use std::fs;
+    use std::path::Path;
+
+    let read = |dir: &Path, file: &str| -> Option<String> {
+        fs::read_to_string(dir.join(file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    let read_num = |dir: &Path, file: &str| -> Option<f64> { read(dir, file)?.parse().ok() };
+
+    let mut battery: Option<BatteryStatus> = None;
+    let mut ac_online: Option<bool> = None;
+
+    for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let dir = entry.path();
+        match read(&dir, "type").as_deref() {
+            Some("Battery") if battery.is_none() => {
+                let Some(charge) = read_num(&dir, "capacity") else {
+                    continue;
+                };
+                // power_now is in µW; otherwise derive it from µA × µV.
+                let power_watts = read_num(&dir, "power_now")
+                    .map(|uw| uw / 1_000_000.0)
+                    .or_else(|| {
+                        let current = read_num(&dir, "current_now")?;
+                        let voltage = read_num(&dir, "voltage_now")?;
+                        Some(current * voltage / 1_000_000_000_000.0)
+                    })
+                    .map(|watts| watts.abs() as f32);
+                battery = Some(BatteryStatus {
+                    charge_percent: charge as f32,
+                    state: read(&dir, "status").unwrap_or_else(|| "Unknown".to_string()),
+                    power_watts,
+                    ac_online: None,
+                });
+            }
+            Some("Mains") => {
+                ac_online = Some(ac_online.unwrap_or(false) || read(&dir, "online").as_deref() == Some("1"));
+            }
+            _ => {}
+        }
+    }
+
+    battery.map(|status| BatteryStatus { ac_online, ..status })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_battery() -> Option<BatteryStatus> {
+    None
+}
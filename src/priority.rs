@@ -0,0 +1,143 @@
+use libc::{getpriority, setpriority, PRIO_PROCESS};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriorityStatus {
+    Success,
+    Failed(String),
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IoPriorityClass {
+    RealTime,
+    BestEffort,
+    Idle,
+}
+
+#[cfg(target_os = "linux")]
+impl IoPriorityClass {
+    pub fn label(self) -> &'static str {
+        match self {
+            IoPriorityClass::RealTime => "Real-time",
+            IoPriorityClass::BestEffort => "Best-effort",
+            IoPriorityClass::Idle => "Idle",
+        }
+    }
+
+    fn as_raw(self) -> i32 {
+        match self {
+            IoPriorityClass::RealTime => 1,
+            IoPriorityClass::BestEffort => 2,
+            IoPriorityClass::Idle => 3,
+        }
+    }
+}
+
+pub fn get_nice(pid: u32) -> Option<i32> {
+    // getpriority can legitimately return -1, so errno has to be cleared and
+    // checked to tell that apart from a failure.
+    unsafe {
+        *errno_location() = 0;
+        let nice = getpriority(PRIO_PROCESS as _, pid as _);
+        if nice == -1 && *errno_location() != 0 {
+            return None;
+        }
+        Some(nice)
+    }
+}
+
+pub fn set_nice(pid: u32, nice: i32) -> PriorityStatus {
+    unsafe {
+        let result = setpriority(PRIO_PROCESS as _, pid as _, nice.clamp(-20, 19));
+        if result != 0 {
+            let error = std::io::Error::last_os_error();
+            return PriorityStatus::Failed(format!("Failed to set nice value: {}", error));
+        }
+    }
+    PriorityStatus::Success
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_affinity(pid: u32) -> Option<Vec<usize>> {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        let result = libc::sched_getaffinity(
+            pid as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &mut set,
+        );
+        if result != 0 {
+            return None;
+        }
+        Some(
+            (0..libc::CPU_SETSIZE as usize)
+                .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_affinity(pid: u32, cpus: &[usize]) -> PriorityStatus {
+    if cpus.is_empty() {
+        return PriorityStatus::Failed("At least one CPU must be selected".to_string());
+    }
+
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for &cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        let result = libc::sched_setaffinity(
+            pid as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &set,
+        );
+        if result != 0 {
+            let error = std::io::Error::last_os_error();
+            return PriorityStatus::Failed(format!("Failed to set CPU affinity: {}", error));
+        }
+    }
+    PriorityStatus::Success
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_io_priority(pid: u32, class: IoPriorityClass, level: u8) -> PriorityStatus {
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: i32 = 13;
+
+    let ioprio = (class.as_raw() << IOPRIO_CLASS_SHIFT) | i32::from(level.min(7));
+    unsafe {
+        let result = libc::syscall(
+            libc::SYS_ioprio_set,
+            IOPRIO_WHO_PROCESS,
+            pid as libc::c_int,
+            ioprio,
+        );
+        if result != 0 {
+            let error = std::io::Error::last_os_error();
+            return PriorityStatus::Failed(format!("Failed to set I/O priority: {}", error));
+        }
+    }
+    PriorityStatus::Success
+}
+
+/// Writes `/proc/<pid>/oom_score_adj`; lowering it below the current value
+/// requires CAP_SYS_RESOURCE.
+#[cfg(target_os = "linux")]
+pub fn set_oom_score_adj(pid: u32, adj: i32) -> PriorityStatus {
+    match std::fs::write(format!("/proc/{}/oom_score_adj", pid), adj.clamp(-1000, 1000).to_string()) {
+        Ok(()) => PriorityStatus::Success,
+        Err(error) => PriorityStatus::Failed(format!("Failed to set oom_score_adj: {}", error)),
+    }
+}
+
+#[cfg(target_os = "linux")]
+unsafe fn errno_location() -> *mut libc::c_int {
+    libc::__errno_location()
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn errno_location() -> *mut libc::c_int {
+    libc::__error()
+}
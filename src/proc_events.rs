@@ -0,0 +1,426 @@
+// Exec/exit tracking for processes too short-lived to show up in a refresh.
+// On Linux the kernel process connector reports every exec and exit as it
+// happens (this needs CAP_NET_ADMIN); everywhere else, or when the connector
+// cannot be opened, events are derived by diffing PID sets each refresh.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+const MAX_EXITED: usize = 200;
+const MAX_EVENTS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventSource {
+    Connector,
+    PidDiff,
+}
+
+impl EventSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            EventSource::Connector => "kernel process connector",
+            EventSource::PidDiff => "PID diff per refresh (misses processes shorter than one refresh)",
+        }
+    }
+}
+
+/// Raw events delivered by the connector thread.
+enum ConnectorEvent {
+    Exec {
+        pid: u32,
+        at: Instant,
+        name: Option<String>,
+        memory_mb: Option<f64>,
+    },
+    Exit {
+        pid: u32,
+        at: Instant,
+    },
+}
+
+/// One live process as seen by a refresh.
+pub struct ProcessSample<'a> {
+    pub pid: u32,
+    pub name: &'a str,
+    pub parent: Option<u32>,
+    pub memory_mb: f64,
+}
+
+struct TrackedProcess {
+    name: String,
+    parent: Option<u32>,
+    started: Instant,
+    peak_memory_mb: f64,
+    last_memory_mb: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExitedProcess {
+    pub pid: u32,
+    pub name: String,
+    pub lifetime: Duration,
+    pub peak_memory_mb: f64,
+    pub exited_at: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProcessEventKind {
+    Started,
+    Exited,
+}
+
+/// Entry in the start/exit log. `lifetime` and the final memory reading are
+/// only meaningful for exits.
+#[derive(Debug, Clone)]
+pub struct ProcessEvent {
+    pub kind: ProcessEventKind,
+    pub timestamp: u64,
+    pub pid: u32,
+    pub name: String,
+    pub parent: Option<u32>,
+    pub lifetime: Option<Duration>,
+    pub memory_mb: f64,
+}
+
+pub struct ProcessTracker {
+    source: EventSource,
+    rx: Option<Receiver<ConnectorEvent>>,
+    live: HashMap<u32, TrackedProcess>,
+    recently_exited: VecDeque<ExitedProcess>,
+    events: VecDeque<ProcessEvent>,
+    churn_history: Vec<(f64, f64)>,
+    last_update: Option<Instant>,
+}
+
+impl ProcessTracker {
+    pub fn new() -> Self {
+        match connector::spawn_listener() {
+            Some(rx) => Self {
+                source: EventSource::Connector,
+                rx: Some(rx),
+                ..Self::with_pid_diff()
+            },
+            None => Self::with_pid_diff(),
+        }
+    }
+
+    /// Tracker that only diffs PID sets, without the process connector.
+    pub fn with_pid_diff() -> Self {
+        Self {
+            source: EventSource::PidDiff,
+            rx: None,
+            live: HashMap::new(),
+            recently_exited: VecDeque::with_capacity(MAX_EXITED),
+            events: VecDeque::with_capacity(MAX_EVENTS),
+            churn_history: Vec::with_capacity(300),
+            last_update: None,
+        }
+    }
+
+    /// Folds connector events and the current process list into the tracker.
+    pub fn update<'a>(
+        &mut self,
+        now: Instant,
+        elapsed: f64,
+        processes: impl Iterator<Item = ProcessSample<'a>>,
+    ) {
+        let mut events = 0usize;
+
+        if let Some(rx) = &self.rx {
+            let pending: Vec<ConnectorEvent> = rx.try_iter().collect();
+            for event in pending {
+                match event {
+                    ConnectorEvent::Exec {
+                        pid,
+                        at,
+                        name,
+                        memory_mb,
+                    } => {
+                        events += 1;
+                        let tracked = self.live.entry(pid).or_insert(TrackedProcess {
+                            name: String::new(),
+                            parent: None,
+                            started: at,
+                            peak_memory_mb: 0.0,
+                            last_memory_mb: 0.0,
+                        });
+                        // exec replaces the image of an existing PID, so the
+                        // name and lifetime start over.
+                        tracked.name = name.unwrap_or_else(|| "?".to_string());
+                        tracked.started = at;
+                        tracked.peak_memory_mb = memory_mb.unwrap_or(0.0);
+                        tracked.last_memory_mb = tracked.peak_memory_mb;
+                        let started = ProcessEvent {
+                            kind: ProcessEventKind::Started,
+                            timestamp: crate::audit::unix_now(),
+                            pid,
+                            name: tracked.name.clone(),
+                            parent: tracked.parent,
+                            lifetime: None,
+                            memory_mb: tracked.last_memory_mb,
+                        };
+                        self.push_event(started);
+                    }
+                    ConnectorEvent::Exit { pid, at } => {
+                        if let Some(tracked) = self.live.remove(&pid) {
+                            events += 1;
+                            self.push_exited(pid, tracked, at);
+                        }
+                    }
+                }
+            }
+        }
+
+        let first_update = self.last_update.is_none();
+        let mut seen: HashSet<u32> = HashSet::new();
+        for sample in processes {
+            seen.insert(sample.pid);
+            match self.live.get_mut(&sample.pid) {
+                Some(tracked) => {
+                    tracked.peak_memory_mb = tracked.peak_memory_mb.max(sample.memory_mb);
+                    tracked.last_memory_mb = sample.memory_mb;
+                    tracked.parent = sample.parent;
+                    if tracked.name == "?" {
+                        tracked.name = sample.name.to_string();
+                    }
+                }
+                None => {
+                    // The first refresh only seeds the table; everything after
+                    // it that the connector did not report is a new process.
+                    if !first_update {
+                        if self.source == EventSource::PidDiff {
+                            events += 1;
+                        }
+                        self.push_event(ProcessEvent {
+                            kind: ProcessEventKind::Started,
+                            timestamp: crate::audit::unix_now(),
+                            pid: sample.pid,
+                            name: sample.name.to_string(),
+                            parent: sample.parent,
+                            lifetime: None,
+                            memory_mb: sample.memory_mb,
+                        });
+                    }
+                    self.live.insert(
+                        sample.pid,
+                        TrackedProcess {
+                            name: sample.name.to_string(),
+                            parent: sample.parent,
+                            started: now,
+                            peak_memory_mb: sample.memory_mb,
+                            last_memory_mb: sample.memory_mb,
+                        },
+                    );
+                }
+            }
+        }
+
+        // Exits the connector missed (or every exit, in PID-diff mode). A
+        // connector-tracked process that exec'd after this refresh's snapshot
+        // is kept until the next refresh confirms it is gone.
+        let gone: Vec<u32> = self
+            .live
+            .iter()
+            .filter(|(pid, tracked)| {
+                let recently_started = self.source == EventSource::Connector
+                    && now.saturating_duration_since(tracked.started) < Duration::from_secs(2);
+                !seen.contains(pid) && !recently_started
+            })
+            .map(|(pid, _)| *pid)
+            .collect();
+        for pid in gone {
+            if let Some(tracked) = self.live.remove(&pid) {
+                events += 1;
+                self.push_exited(pid, tracked, now);
+            }
+        }
+
+        if let Some(last) = self.last_update {
+            let interval = now.duration_since(last).as_secs_f64().max(0.001);
+            self.churn_history.push((elapsed, events as f64 / interval));
+            if self.churn_history.len() > 300 {
+                self.churn_history.remove(0);
+            }
+        }
+        self.last_update = Some(now);
+    }
+
+    fn push_exited(&mut self, pid: u32, tracked: TrackedProcess, at: Instant) {
+        let lifetime = at.saturating_duration_since(tracked.started);
+        self.push_event(ProcessEvent {
+            kind: ProcessEventKind::Exited,
+            timestamp: crate::audit::unix_now(),
+            pid,
+            name: tracked.name.clone(),
+            parent: tracked.parent,
+            lifetime: Some(lifetime),
+            memory_mb: tracked.last_memory_mb,
+        });
+        self.recently_exited.push_front(ExitedProcess {
+            pid,
+            name: tracked.name,
+            lifetime,
+            peak_memory_mb: tracked.peak_memory_mb,
+            exited_at: at,
+        });
+        self.recently_exited.truncate(MAX_EXITED);
+    }
+
+    fn push_event(&mut self, event: ProcessEvent) {
+        self.events.push_front(event);
+        self.events.truncate(MAX_EVENTS);
+    }
+
+    pub fn source(&self) -> EventSource {
+        self.source
+    }
+
+    /// Most recent first.
+    pub fn recently_exited(&self) -> &VecDeque<ExitedProcess> {
+        &self.recently_exited
+    }
+
+    /// Start/exit log, most recent first.
+    pub fn events(&self) -> &VecDeque<ProcessEvent> {
+        &self.events
+    }
+
+    pub fn get_churn_history(&self) -> &[(f64, f64)] {
+        &self.churn_history
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod connector {
+    use super::ConnectorEvent;
+    use std::mem;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::thread;
+    use std::time::Instant;
+
+    const NLMSG_HDRLEN: usize = 16;
+    const CN_MSG_LEN: usize = 20;
+    const EVENT_OFFSET: usize = NLMSG_HDRLEN + CN_MSG_LEN;
+    // proc_event: what (u32), cpu (u32), timestamp_ns (u64), then event data.
+    const EVENT_DATA_OFFSET: usize = EVENT_OFFSET + 16;
+
+    /// Subscribes to the process connector and forwards exec/exit events.
+    /// Returns `None` if the socket cannot be opened or subscribed, which is
+    /// the normal case without CAP_NET_ADMIN.
+    pub(super) fn spawn_listener() -> Option<Receiver<ConnectorEvent>> {
+        let fd = unsafe { open_socket()? };
+
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 4096];
+            loop {
+                let n = unsafe { libc::recv(fd, buffer.as_mut_ptr().cast(), buffer.len(), 0) };
+                if n <= 0 {
+                    if std::io::Error::last_os_error().raw_os_error() == Some(libc::ENOBUFS) {
+                        continue;
+                    }
+                    break;
+                }
+                if let Some(event) = parse_event(&buffer[..n as usize]) {
+                    if tx.send(event).is_err() {
+                        break;
+                    }
+                }
+            }
+            unsafe {
+                libc::close(fd);
+            }
+        });
+
+        Some(rx)
+    }
+
+    unsafe fn open_socket() -> Option<i32> {
+        let fd = libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM, libc::NETLINK_CONNECTOR);
+        if fd < 0 {
+            return None;
+        }
+
+        let mut address: libc::sockaddr_nl = mem::zeroed();
+        address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        address.nl_pid = libc::getpid() as u32;
+        address.nl_groups = libc::CN_IDX_PROC;
+        if libc::bind(
+            fd,
+            (&address as *const libc::sockaddr_nl).cast(),
+            mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        ) != 0
+        {
+            libc::close(fd);
+            return None;
+        }
+
+        // nlmsghdr + cn_msg + PROC_CN_MCAST_LISTEN
+        let total = NLMSG_HDRLEN + CN_MSG_LEN + 4;
+        let mut message = Vec::with_capacity(total);
+        message.extend_from_slice(&(total as u32).to_ne_bytes());
+        message.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+        message.extend_from_slice(&0u16.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes());
+        message.extend_from_slice(&(libc::getpid() as u32).to_ne_bytes());
+        message.extend_from_slice(&libc::CN_IDX_PROC.to_ne_bytes());
+        message.extend_from_slice(&libc::CN_VAL_PROC.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes());
+        message.extend_from_slice(&4u16.to_ne_bytes());
+        message.extend_from_slice(&0u16.to_ne_bytes());
+        message.extend_from_slice(&libc::PROC_CN_MCAST_LISTEN.to_ne_bytes());
+
+        if libc::send(fd, message.as_ptr().cast(), message.len(), 0) < 0 {
+            libc::close(fd);
+            return None;
+        }
+        Some(fd)
+    }
+
+    fn parse_event(buffer: &[u8]) -> Option<ConnectorEvent> {
+        let read_u32 = |offset: usize| -> Option<u32> {
+            Some(u32::from_ne_bytes(buffer.get(offset..offset + 4)?.try_into().ok()?))
+        };
+
+        let what = read_u32(EVENT_OFFSET)?;
+        let pid = read_u32(EVENT_DATA_OFFSET)?;
+        let tgid = read_u32(EVENT_DATA_OFFSET + 4)?;
+        // Thread events carry pid != tgid; only whole processes are tracked.
+        if pid != tgid {
+            return None;
+        }
+
+        let at = Instant::now();
+        match what {
+            libc::PROC_EVENT_EXEC => Some(ConnectorEvent::Exec {
+                pid,
+                at,
+                name: std::fs::read_to_string(format!("/proc/{}/comm", pid))
+                    .ok()
+                    .map(|name| name.trim().to_string()),
+                memory_mb: read_rss_mb(pid),
+            }),
+            libc::PROC_EVENT_EXIT => Some(ConnectorEvent::Exit { pid, at }),
+            _ => None,
+        }
+    }
+
+    fn read_rss_mb(pid: u32) -> Option<f64> {
+        let statm = std::fs::read_to_string(format!("/proc/{}/statm", pid)).ok()?;
+        let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+        Some((pages * page_size) as f64 / 1_048_576.0)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod connector {
+    use super::ConnectorEvent;
+    use std::sync::mpsc::Receiver;
+
+    pub(super) fn spawn_listener() -> Option<Receiver<ConnectorEvent>> {
+        None
+    }
+}
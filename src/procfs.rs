@@ -0,0 +1,490 @@
+// Native Linux process source. Every refresh reads only /proc/<pid>/stat and
+// /proc/<pid>/statm into a reused buffer; the executable, owner, I/O counters
+// and OOM scores are read when a PID first appears. I/O counters are re-read
+// only for processes whose CPU time or RSS changed since the previous pass,
+// and OOM scores for those only every few passes.
+
+use crate::source::{OOM_REFRESH_TICKS, ProcessSource, SamplingCost, SourceProcess, SystemSample, UnresolvedUids};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::unix::fs::MetadataExt;
+use std::time::Instant;
+use sysinfo::Users;
+
+/// Per-PID counters from the previous pass, parallel to `processes`.
+struct PidState {
+    start_time: u64,
+    cpu_ticks: u64,
+    rss_pages: u64,
+    io_read: u64,
+    io_written: u64,
+    /// When `io_read` and `io_written` were read; passes that skip a quiet
+    /// process leave it behind the current one.
+    io_sampled: Option<Instant>,
+    /// Set when the OOM scores must be read on the next pass regardless.
+    oom_stale: bool,
+    seen: u64,
+}
+
+pub struct ProcfsSource {
+    processes: Vec<SourceProcess>,
+    states: Vec<PidState>,
+    index: HashMap<u32, usize>,
+    user_names: HashMap<u32, String>,
+    /// Set when a newly seen process runs as a UID missing from `user_names`
+    /// that is worth reading the account list again for.
+    unknown_user: bool,
+    unresolved_uids: UnresolvedUids,
+    system: SystemSample,
+    cpu_totals: Option<(u64, u64)>,
+    last_refresh: Option<Instant>,
+    generation: u64,
+    files_read: usize,
+    cost: SamplingCost,
+    buffer: String,
+    stat_buffer: String,
+    path: String,
+    ticks_per_second: f64,
+    page_size: u64,
+}
+
+/// Fields of /proc/<pid>/stat the source uses.
+struct Stat<'a> {
+    name: &'a str,
+    parent: u32,
+    session: u32,
+    cpu_ticks: u64,
+    start_time: u64,
+}
+
+impl ProcfsSource {
+    pub fn new() -> Self {
+        let mut source = Self {
+            processes: Vec::new(),
+            states: Vec::new(),
+            index: HashMap::new(),
+            user_names: load_user_names(),
+            unknown_user: false,
+            unresolved_uids: UnresolvedUids::new(Instant::now()),
+            system: SystemSample::default(),
+            cpu_totals: None,
+            last_refresh: None,
+            generation: 0,
+            files_read: 0,
+            cost: SamplingCost::default(),
+            buffer: String::with_capacity(4096),
+            stat_buffer: String::with_capacity(512),
+            path: String::with_capacity(64),
+            ticks_per_second: unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64,
+            page_size: unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64,
+        };
+        source.refresh();
+        source
+    }
+
+    /// Reads `/proc/<pid>/<file>` into `self.buffer`.
+    fn read(&mut self, pid: Option<u32>, file: &str) -> bool {
+        self.files_read += 1;
+        read_proc(&mut self.path, &mut self.buffer, pid, file)
+    }
+
+    fn refresh_system(&mut self) {
+        if self.read(None, "stat") {
+            let mut cpu_count = 0;
+            let mut totals = None;
+            for line in self.buffer.lines() {
+                let Some(rest) = line.strip_prefix("cpu") else {
+                    break;
+                };
+                if rest.starts_with(' ') {
+                    // user nice system idle iowait irq softirq steal
+                    let fields: Vec<u64> = rest
+                        .split_whitespace()
+                        .take(8)
+                        .filter_map(|field| field.parse().ok())
+                        .collect();
+                    if fields.len() >= 5 {
+                        totals = Some((fields.iter().sum::<u64>(), fields[3] + fields[4]));
+                    }
+                } else {
+                    cpu_count += 1;
+                }
+            }
+            if let Some((total, idle)) = totals {
+                if let Some((last_total, last_idle)) = self.cpu_totals {
+                    let total_delta = total.saturating_sub(last_total);
+                    let idle_delta = idle.saturating_sub(last_idle);
+                    if total_delta > 0 {
+                        self.system.cpu_usage =
+                            (1.0 - idle_delta as f32 / total_delta as f32) * 100.0;
+                    }
+                }
+                self.cpu_totals = Some((total, idle));
+            }
+            self.system.cpu_count = cpu_count;
+        }
+
+        if self.read(None, "meminfo") {
+            let mut total = 0;
+            let mut available = 0;
+            for line in self.buffer.lines() {
+                let kb = |line: &str| {
+                    line.split_whitespace()
+                        .nth(1)
+                        .and_then(|value| value.parse::<u64>().ok())
+                        .unwrap_or(0)
+                        * 1024
+                };
+                if line.starts_with("MemTotal:") {
+                    total = kb(line);
+                } else if line.starts_with("MemAvailable:") {
+                    available = kb(line);
+                }
+            }
+            self.system.total_memory = total;
+            self.system.available_memory = available;
+            self.system.used_memory = total.saturating_sub(available);
+        }
+    }
+
+    /// Reads and parses the stat and statm files for one PID. Returns `None`
+    /// if the process vanished in between.
+    fn sample(&mut self, pid: u32, now: Instant, interval: f64) -> Option<()> {
+        if !self.read(Some(pid), "statm") {
+            return None;
+        }
+        let rss_pages: u64 = self.buffer.split_whitespace().nth(1)?.parse().ok()?;
+
+        // stat gets its own buffer because `apply` reads further files
+        // through `self.buffer` while the parsed name still borrows it.
+        let mut stat_buffer = std::mem::take(&mut self.stat_buffer);
+        self.files_read += 1;
+        let result = if read_proc(&mut self.path, &mut stat_buffer, Some(pid), "stat") {
+            parse_stat(&stat_buffer).map(|stat| self.apply(pid, &stat, rss_pages, now, interval))
+        } else {
+            None
+        };
+        self.stat_buffer = stat_buffer;
+        result
+    }
+
+    fn apply(&mut self, pid: u32, stat: &Stat, rss_pages: u64, now: Instant, interval: f64) {
+        let known = self
+            .index
+            .get(&pid)
+            .copied()
+            .filter(|&slot| self.states[slot].start_time == stat.start_time);
+
+        let slot = match known {
+            Some(slot) => slot,
+            None => {
+                // New process, or a recycled PID: drop the old slot's data.
+                let uid = fs::metadata(format!("/proc/{}", pid)).ok().map(|meta| meta.uid());
+                let process = SourceProcess {
+                    pid,
+                    name: stat.name.to_string(),
+                    exe: read_exe(pid),
+                    uid,
+                    user: uid.and_then(|uid| self.user_names.get(&uid).cloned()),
+                    ..Default::default()
+                };
+                self.unknown_user |= process.user.is_none()
+                    && uid.is_some_and(|uid| self.unresolved_uids.worth_reload(uid, now));
+                self.files_read += 2;
+                let state = PidState {
+                    start_time: stat.start_time,
+                    cpu_ticks: stat.cpu_ticks,
+                    rss_pages: 0,
+                    io_read: 0,
+                    io_written: 0,
+                    io_sampled: None,
+                    oom_stale: false,
+                    seen: 0,
+                };
+                match self.index.get(&pid).copied() {
+                    Some(slot) => {
+                        self.processes[slot] = process;
+                        self.states[slot] = state;
+                        slot
+                    }
+                    None => {
+                        self.processes.push(process);
+                        self.states.push(state);
+                        self.index.insert(pid, self.processes.len() - 1);
+                        self.processes.len() - 1
+                    }
+                }
+            }
+        };
+
+        let state = &mut self.states[slot];
+        let fresh = state.seen == 0;
+        let cpu_delta = stat.cpu_ticks.saturating_sub(state.cpu_ticks);
+        let changed = fresh || cpu_delta > 0 || rss_pages != state.rss_pages;
+        state.cpu_ticks = stat.cpu_ticks;
+        state.rss_pages = rss_pages;
+        state.seen = self.generation;
+        let oom_stale = std::mem::take(&mut state.oom_stale);
+
+        let process = &mut self.processes[slot];
+        if process.name != stat.name {
+            // The process exec'd something else.
+            process.name = stat.name.to_string();
+            process.exe = read_exe(pid);
+        }
+        process.parent = (stat.parent != 0).then_some(stat.parent);
+        process.session_id = Some(stat.session);
+        process.memory_bytes = rss_pages * self.page_size;
+        process.cpu_usage = if fresh || interval <= 0.0 {
+            0.0
+        } else {
+            (cpu_delta as f64 / self.ticks_per_second / interval * 100.0) as f32
+        };
+        process.disk_read_bytes = 0;
+        process.disk_written_bytes = 0;
+
+        if changed || oom_stale {
+            self.refresh_extras(slot, fresh, oom_stale, now, interval);
+        }
+    }
+
+    /// I/O counters and OOM scores, only worth re-reading for processes that
+    /// did something since the last pass.
+    fn refresh_extras(&mut self, slot: usize, fresh: bool, oom_stale: bool, now: Instant, interval: f64) {
+        let pid = self.processes[slot].pid;
+
+        // /proc/<pid>/io is only readable for our own processes unless root.
+        if self.read(Some(pid), "io") {
+            let mut read = None;
+            let mut written = None;
+            for line in self.buffer.lines() {
+                if let Some(value) = line.strip_prefix("read_bytes: ") {
+                    read = value.trim().parse::<u64>().ok();
+                } else if let Some(value) = line.strip_prefix("write_bytes: ") {
+                    written = value.trim().parse::<u64>().ok();
+                }
+            }
+            let state = &mut self.states[slot];
+            if let (Some(read), Some(written)) = (read, written) {
+                if let Some(sampled) = state.io_sampled.filter(|_| !fresh) {
+                    // The counters may cover several skipped passes; scale
+                    // them down to one interval's worth so rates stay right.
+                    let elapsed = now.duration_since(sampled).as_secs_f64();
+                    let scale = if elapsed > interval && interval > 0.0 { interval / elapsed } else { 1.0 };
+                    let per_interval = |bytes: u64| (bytes as f64 * scale) as u64;
+                    self.processes[slot].disk_read_bytes = per_interval(read.saturating_sub(state.io_read));
+                    self.processes[slot].disk_written_bytes =
+                        per_interval(written.saturating_sub(state.io_written));
+                }
+                state.io_read = read;
+                state.io_written = written;
+                state.io_sampled = Some(now);
+            }
+        }
+
+        if !fresh && !oom_stale && !self.generation.is_multiple_of(OOM_REFRESH_TICKS) {
+            return;
+        }
+        self.processes[slot].oom_score = self
+            .read(Some(pid), "oom_score")
+            .then(|| self.buffer.trim().parse().ok())
+            .flatten();
+        self.processes[slot].oom_score_adj = self
+            .read(Some(pid), "oom_score_adj")
+            .then(|| self.buffer.trim().parse().ok())
+            .flatten();
+    }
+
+    /// Drops PIDs that were not seen in the current pass.
+    fn prune(&mut self) {
+        let generation = self.generation;
+        if self.states.iter().all(|state| state.seen == generation) {
+            return;
+        }
+        let mut keep = self.states.iter().map(|state| state.seen == generation);
+        self.processes.retain(|_| keep.next().unwrap_or(false));
+        self.states.retain(|state| state.seen == generation);
+        self.index = self
+            .processes
+            .iter()
+            .enumerate()
+            .map(|(slot, process)| (process.pid, slot))
+            .collect();
+    }
+
+    fn resolve_users(&mut self, now: Instant) {
+        if !std::mem::take(&mut self.unknown_user) {
+            return;
+        }
+        self.user_names = load_user_names();
+        for process in &mut self.processes {
+            if process.user.is_none() {
+                process.user = process.uid.and_then(|uid| self.user_names.get(&uid).cloned());
+            }
+        }
+        let unresolved = self
+            .processes
+            .iter()
+            .filter(|process| process.user.is_none())
+            .filter_map(|process| process.uid);
+        self.unresolved_uids.reloaded(unresolved, now);
+    }
+}
+
+impl ProcessSource for ProcfsSource {
+    fn refresh(&mut self) {
+        let started = Instant::now();
+        let interval = self
+            .last_refresh
+            .map(|last| started.duration_since(last).as_secs_f64())
+            .unwrap_or(0.0);
+        self.generation += 1;
+        self.files_read = 0;
+
+        self.refresh_system();
+
+        if let Ok(entries) = fs::read_dir("/proc") {
+            for entry in entries.flatten() {
+                let Some(pid) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.parse::<u32>().ok())
+                else {
+                    continue;
+                };
+                let _ = self.sample(pid, started, interval);
+            }
+        }
+        self.prune();
+        self.resolve_users(started);
+
+        self.last_refresh = Some(started);
+        self.cost = SamplingCost {
+            duration: started.elapsed(),
+            processes_scanned: self.processes.len(),
+            files_read: Some(self.files_read),
+        };
+    }
+
+    fn processes(&self) -> &[SourceProcess] {
+        &self.processes
+    }
+
+    fn system(&self) -> SystemSample {
+        self.system
+    }
+
+    fn cost(&self) -> SamplingCost {
+        self.cost
+    }
+
+    fn invalidate_oom_scores(&mut self, pid: u32) {
+        if let Some(&slot) = self.index.get(&pid) {
+            self.states[slot].oom_stale = true;
+        }
+    }
+}
+
+/// Parses `pid (comm) state ppid pgrp session ...`. The command name may
+/// contain spaces and parentheses, so fields are counted from the last ')'.
+fn parse_stat(line: &str) -> Option<Stat<'_>> {
+    let open = line.find('(')?;
+    let close = line.rfind(')')?;
+    let name = line.get(open + 1..close)?;
+    let mut fields = line.get(close + 2..)?.split_whitespace();
+
+    // Field numbers from proc(5), starting at 3 (state).
+    let mut nth = |skip: usize| -> Option<u64> { fields.nth(skip)?.parse().ok() };
+    let parent = nth(1)?; // 4 ppid
+    let session = nth(1)?; // 6 session
+    let utime = nth(7)?; // 14 utime
+    let stime = nth(0)?; // 15 stime
+    let start_time = nth(6)?; // 22 starttime
+
+    Some(Stat {
+        name,
+        parent: parent as u32,
+        session: session as u32,
+        cpu_ticks: utime + stime,
+        start_time,
+    })
+}
+
+fn read_proc(path: &mut String, buffer: &mut String, pid: Option<u32>, file: &str) -> bool {
+    path.clear();
+    let _ = match pid {
+        Some(pid) => write!(path, "/proc/{}/{}", pid, file),
+        None => write!(path, "/proc/{}", file),
+    };
+    buffer.clear();
+    File::open(path.as_str())
+        .and_then(|mut file| file.read_to_string(buffer))
+        .is_ok()
+}
+
+fn read_exe(pid: u32) -> Option<String> {
+    fs::read_link(format!("/proc/{}/exe", pid))
+        .ok()
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+fn load_user_names() -> HashMap<u32, String> {
+    Users::new_with_refreshed_list()
+        .list()
+        .iter()
+        .map(|user| (**user.id(), user.name().to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stat_handles_parentheses_in_names() {
+        let line = "4242 (tmux: (server) x) S 1 4242 4240 0 -1 4194560 1311 0 0 0 \
+                    37 12 0 0 20 0 1 0 987654 10465280 1024 18446744073709551615";
+        let stat = parse_stat(line).unwrap();
+        assert_eq!(stat.name, "tmux: (server) x");
+        assert_eq!(stat.parent, 1);
+        assert_eq!(stat.session, 4240);
+        assert_eq!(stat.cpu_ticks, 49);
+        assert_eq!(stat.start_time, 987654);
+    }
+
+    #[test]
+    fn unknown_uids_reload_the_account_list_once() {
+        // A container UID with no passwd entry.
+        const UID: u32 = 3_999_999_999;
+        let mut source = ProcfsSource::new();
+        let now = Instant::now();
+        source.processes.push(SourceProcess {
+            pid: u32::MAX,
+            uid: Some(UID),
+            ..Default::default()
+        });
+        source.unknown_user = true;
+        source.resolve_users(now);
+
+        // Further processes running as it do not read the list again until a
+        // minute has passed; a different unknown UID still does.
+        let soon = now + std::time::Duration::from_secs(1);
+        assert!(!source.unresolved_uids.worth_reload(UID, soon));
+        assert!(source.unresolved_uids.worth_reload(UID + 1, soon));
+        assert!(source.unresolved_uids.worth_reload(UID, now + std::time::Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn finds_its_own_process() {
+        let mut source = ProcfsSource::new();
+        source.refresh();
+        let pid = std::process::id();
+        let me = source.processes().iter().find(|p| p.pid == pid).unwrap();
+        assert!(me.memory_bytes > 0);
+        assert_eq!(me.uid, Some(unsafe { libc::getuid() }));
+        assert!(source.system().total_memory >= source.system().used_memory);
+        assert!(source.cost().files_read.unwrap() >= 2 * source.processes().len());
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+/// Processes that kill actions refuse to signal unless the user explicitly
+/// overrides the safelist from the settings window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtectionRules {
+    pub names: Vec<String>,
+    pub paths: Vec<String>,
+    pub pids: Vec<u32>,
+    pub uids: Vec<u32>,
+}
+
+impl Default for ProtectionRules {
+    fn default() -> Self {
+        Self {
+            names: [
+                "init",
+                "systemd",
+                "launchd",
+                "kernel_task",
+                "WindowServer",
+                "loginwindow",
+                "Xorg",
+                "Xwayland",
+                "gnome-shell",
+                "kwin_wayland",
+                "sshd",
+                "dbus-daemon",
+                "memwatch",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            paths: Vec::new(),
+            pids: vec![0, 1],
+            uids: Vec::new(),
+        }
+    }
+}
+
+impl ProtectionRules {
+    /// Returns why `pid` is protected, or `None` if it may be signalled.
+    /// memwatch's own PID is always protected regardless of the configured lists.
+    pub fn reason(&self, pid: u32, name: &str, exe: Option<&str>, uid: Option<u32>) -> Option<String> {
+        if pid == std::process::id() {
+            return Some("memwatch cannot signal itself".to_string());
+        }
+        if self.pids.contains(&pid) {
+            return Some(format!("PID {} is on the protection list", pid));
+        }
+        if self.names.iter().any(|n| n == name) {
+            return Some(format!("'{}' is on the protection list", name));
+        }
+        if let Some(exe) = exe {
+            if let Some(path) = self
+                .paths
+                .iter()
+                .find(|path| !path.is_empty() && exe.starts_with(path.as_str()))
+            {
+                return Some(format!("Executables under '{}' are protected", path));
+            }
+        }
+        if let Some(uid) = uid {
+            if self.uids.contains(&uid) {
+                return Some(format!("Processes owned by UID {} are protected", uid));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty() -> ProtectionRules {
+        ProtectionRules {
+            names: Vec::new(),
+            paths: Vec::new(),
+            pids: Vec::new(),
+            uids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn defaults_protect_pids_zero_and_one() {
+        let rules = ProtectionRules::default();
+        assert!(rules.reason(0, "swapper", None, None).is_some());
+        assert!(rules.reason(1, "anything", None, None).is_some());
+        assert_eq!(rules.reason(4242, "worker", None, None), None);
+    }
+
+    #[test]
+    fn matches_names_paths_pids_and_uids() {
+        let rules = ProtectionRules {
+            names: vec!["sshd".to_string()],
+            paths: vec!["/usr/sbin/".to_string(), String::new()],
+            pids: vec![77],
+            uids: vec![0],
+        };
+        assert!(rules.reason(4242, "sshd", None, None).is_some());
+        assert_eq!(rules.reason(4242, "sshd-session", None, None), None);
+        assert!(rules.reason(4242, "cron", Some("/usr/sbin/cron"), None).is_some());
+        // An empty path entry must not match every executable.
+        assert_eq!(rules.reason(4242, "vim", Some("/usr/bin/vim"), None), None);
+        assert!(rules.reason(77, "worker", None, None).is_some());
+        assert!(rules.reason(4242, "worker", None, Some(0)).is_some());
+        assert_eq!(rules.reason(4242, "worker", None, Some(1000)), None);
+    }
+
+    #[test]
+    fn own_pid_is_always_refused() {
+        let reason = empty().reason(std::process::id(), "cargo-test", None, None);
+        assert_eq!(reason.as_deref(), Some("memwatch cannot signal itself"));
+    }
+}
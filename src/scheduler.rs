@@ -0,0 +1,173 @@
+// Adaptive refresh scheduling: sample less often while the window is hidden
+// or the machine is idle, and faster for a while after a sharp change in CPU
+// or memory use.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefreshPolicy {
+    pub adaptive: bool,
+    /// Interval while the window is hidden; history and alerts keep running.
+    pub hidden_interval_ms: u64,
+    /// Interval once CPU has stayed below `idle_cpu_percent` for
+    /// `idle_after_seconds`.
+    pub idle_interval_ms: u64,
+    pub idle_cpu_percent: f32,
+    pub idle_after_seconds: u64,
+    /// Interval for `burst_seconds` after a CPU or memory spike.
+    pub burst_interval_ms: u64,
+    pub burst_cpu_delta: f32,
+    pub burst_memory_delta_mb: f64,
+    pub burst_seconds: u64,
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        Self {
+            adaptive: true,
+            hidden_interval_ms: 5000,
+            idle_interval_ms: 3000,
+            idle_cpu_percent: 5.0,
+            idle_after_seconds: 10,
+            burst_interval_ms: 250,
+            burst_cpu_delta: 25.0,
+            burst_memory_delta_mb: 500.0,
+            burst_seconds: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefreshMode {
+    Normal,
+    Hidden,
+    Idle,
+    Burst,
+}
+
+impl RefreshMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            RefreshMode::Normal => "normal",
+            RefreshMode::Hidden => "hidden",
+            RefreshMode::Idle => "idle",
+            RefreshMode::Burst => "fast, recent spike",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RefreshScheduler {
+    previous: Option<(f32, f64)>,
+    idle_since: Option<Instant>,
+    burst_until: Option<Instant>,
+}
+
+impl RefreshScheduler {
+    /// Feeds the system-wide CPU % and used memory of the latest refresh.
+    pub fn observe(&mut self, policy: &RefreshPolicy, now: Instant, cpu_usage: f32, used_memory_mb: f64) {
+        if let Some((cpu_before, memory_before)) = self.previous {
+            if (cpu_usage - cpu_before).abs() >= policy.burst_cpu_delta
+                || (used_memory_mb - memory_before).abs() >= policy.burst_memory_delta_mb
+            {
+                self.burst_until = Some(now + Duration::from_secs(policy.burst_seconds));
+            }
+        }
+        self.previous = Some((cpu_usage, used_memory_mb));
+
+        if cpu_usage < policy.idle_cpu_percent {
+            self.idle_since.get_or_insert(now);
+        } else {
+            self.idle_since = None;
+        }
+    }
+
+    /// Time until the next refresh. A spike speeds up sampling even while
+    /// hidden, so the OOM guard and disk alerts react promptly.
+    pub fn next_interval(
+        &self,
+        policy: &RefreshPolicy,
+        base_ms: u64,
+        visible: bool,
+        now: Instant,
+    ) -> (Duration, RefreshMode) {
+        let (ms, mode) = if !policy.adaptive {
+            (base_ms, RefreshMode::Normal)
+        } else if self.burst_until.is_some_and(|until| now < until) {
+            (policy.burst_interval_ms.min(base_ms), RefreshMode::Burst)
+        } else if !visible {
+            (policy.hidden_interval_ms.max(base_ms), RefreshMode::Hidden)
+        } else if self
+            .idle_since
+            .is_some_and(|since| now.duration_since(since) >= Duration::from_secs(policy.idle_after_seconds))
+        {
+            (policy.idle_interval_ms.max(base_ms), RefreshMode::Idle)
+        } else {
+            (base_ms, RefreshMode::Normal)
+        };
+        (Duration::from_millis(ms), mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(scheduler: &RefreshScheduler, visible: bool, now: Instant) -> (u64, RefreshMode) {
+        let (interval, mode) = scheduler.next_interval(&RefreshPolicy::default(), 1000, visible, now);
+        (interval.as_millis() as u64, mode)
+    }
+
+    #[test]
+    fn hidden_window_samples_slower() {
+        let scheduler = RefreshScheduler::default();
+        let now = Instant::now();
+        assert_eq!(interval(&scheduler, true, now), (1000, RefreshMode::Normal));
+        assert_eq!(interval(&scheduler, false, now), (5000, RefreshMode::Hidden));
+    }
+
+    #[test]
+    fn backs_off_after_sustained_idle() {
+        let policy = RefreshPolicy::default();
+        let mut scheduler = RefreshScheduler::default();
+        let start = Instant::now();
+
+        scheduler.observe(&policy, start, 2.0, 4000.0);
+        assert_eq!(interval(&scheduler, true, start + Duration::from_secs(5)).1, RefreshMode::Normal);
+        assert_eq!(
+            interval(&scheduler, true, start + Duration::from_secs(10)),
+            (3000, RefreshMode::Idle)
+        );
+
+        scheduler.observe(&policy, start + Duration::from_secs(11), 12.0, 4000.0);
+        assert_eq!(interval(&scheduler, true, start + Duration::from_secs(30)).1, RefreshMode::Normal);
+    }
+
+    #[test]
+    fn spikes_speed_up_even_when_hidden() {
+        let policy = RefreshPolicy::default();
+        let mut scheduler = RefreshScheduler::default();
+        let start = Instant::now();
+
+        scheduler.observe(&policy, start, 10.0, 4000.0);
+        scheduler.observe(&policy, start, 10.0, 4800.0);
+        assert_eq!(interval(&scheduler, false, start), (250, RefreshMode::Burst));
+        assert_eq!(
+            interval(&scheduler, false, start + Duration::from_secs(10)).1,
+            RefreshMode::Hidden
+        );
+    }
+
+    #[test]
+    fn disabled_policy_uses_the_base_interval() {
+        let policy = RefreshPolicy {
+            adaptive: false,
+            ..Default::default()
+        };
+        let scheduler = RefreshScheduler::default();
+        let (interval, mode) = scheduler.next_interval(&policy, 1500, false, Instant::now());
+        assert_eq!((interval, mode), (Duration::from_millis(1500), RefreshMode::Normal));
+    }
+}
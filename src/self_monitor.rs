@@ -0,0 +1,81 @@
+// memwatch's own footprint: RSS and CPU from the process list it already
+// collects, plus how long refreshes and egui frames take.
+
+use crate::metrics::ProcessInfo;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SelfSample {
+    /// Seconds since memwatch started.
+    pub elapsed: f64,
+    pub rss_mb: f64,
+    pub cpu_usage: f32,
+    pub refresh_ms: f64,
+    pub processes_scanned: usize,
+    /// Mean and worst frame time since the previous sample.
+    pub frame_ms: f64,
+    pub frame_max_ms: f64,
+}
+
+pub struct SelfMonitor {
+    pid: u32,
+    start_time: Instant,
+    frame_total: Duration,
+    frame_max: Duration,
+    frames: u32,
+    history: Vec<SelfSample>,
+}
+
+impl SelfMonitor {
+    pub fn new() -> Self {
+        Self {
+            pid: std::process::id(),
+            start_time: Instant::now(),
+            frame_total: Duration::ZERO,
+            frame_max: Duration::ZERO,
+            frames: 0,
+            history: Vec::with_capacity(300),
+        }
+    }
+
+    pub fn record_frame(&mut self, duration: Duration) {
+        self.frame_total += duration;
+        self.frame_max = self.frame_max.max(duration);
+        self.frames += 1;
+    }
+
+    /// Takes a sample after each metrics refresh.
+    pub fn record_refresh(&mut self, processes: &[ProcessInfo], refresh: Duration, processes_scanned: usize) {
+        let own = processes.iter().find(|p| p.pid == self.pid);
+        let frame_ms = if self.frames > 0 {
+            self.frame_total.as_secs_f64() * 1000.0 / self.frames as f64
+        } else {
+            0.0
+        };
+
+        self.history.push(SelfSample {
+            elapsed: self.start_time.elapsed().as_secs_f64(),
+            rss_mb: own.map(|p| p.memory_mb).unwrap_or(0.0),
+            cpu_usage: own.map(|p| p.cpu_usage).unwrap_or(0.0),
+            refresh_ms: refresh.as_secs_f64() * 1000.0,
+            processes_scanned,
+            frame_ms,
+            frame_max_ms: self.frame_max.as_secs_f64() * 1000.0,
+        });
+        if self.history.len() > 300 {
+            self.history.remove(0);
+        }
+
+        self.frame_total = Duration::ZERO;
+        self.frame_max = Duration::ZERO;
+        self.frames = 0;
+    }
+
+    pub fn latest(&self) -> Option<&SelfSample> {
+        self.history.last()
+    }
+
+    pub fn history(&self) -> &[SelfSample] {
+        &self.history
+    }
+}
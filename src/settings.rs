@@ -1,57 +1,839 @@
+use crate::escalation::EscalationRules;
+use crate::keybindings::KeyBindings;
+use crate::oom_guard::OomGuardSettings;
+use crate::protection::ProtectionRules;
+use crate::scheduler::RefreshPolicy;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Schema version written by this build. Bump it and add a step to
+/// `migrate` whenever a field is renamed or changes meaning.
+pub const SETTINGS_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SortMode {
     Memory,
     Cpu,
+    OomScore,
+    DiskIo,
+}
+
+/// Backend `MetricsCollector` reads processes from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ProcessSourceKind {
+    #[default]
+    Sysinfo,
+    /// Native /proc reader; Linux only.
+    Procfs,
+}
+
+impl ProcessSourceKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ProcessSourceKind::Sysinfo => "sysinfo",
+            ProcessSourceKind::Procfs => "Native /proc",
+        }
+    }
+}
+
+/// Optional process list columns; name and actions are always shown.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnVisibility {
+    pub pid: bool,
+    pub cpu: bool,
+    pub memory: bool,
+    pub disk: bool,
+    /// OOM score and adjustment; Linux only.
+    pub oom: bool,
+}
+
+impl Default for ColumnVisibility {
+    fn default() -> Self {
+        Self {
+            pid: true,
+            cpu: true,
+            memory: true,
+            disk: true,
+            oom: true,
+        }
+    }
+}
+
+/// Which lines the resource chart draws. Filesystem and network lines are
+/// chosen separately through `chart_mount_point` and
+/// `chart_network_interface`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChartSeries {
+    pub cpu: bool,
+    pub memory: bool,
+    pub disk: bool,
+    pub churn: bool,
+    pub temperature: bool,
+    pub battery: bool,
+}
+
+impl Default for ChartSeries {
+    fn default() -> Self {
+        Self {
+            cpu: true,
+            memory: true,
+            disk: true,
+            churn: true,
+            temperature: true,
+            battery: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartLine {
+    Cpu,
+    Memory,
+    DiskIo,
+    Churn,
+    Temperature,
+    Battery,
+}
+
+impl ChartLine {
+    pub const ALL: [ChartLine; 6] = [
+        ChartLine::Cpu,
+        ChartLine::Memory,
+        ChartLine::DiskIo,
+        ChartLine::Churn,
+        ChartLine::Temperature,
+        ChartLine::Battery,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChartLine::Cpu => "CPU",
+            ChartLine::Memory => "Memory",
+            ChartLine::DiskIo => "Disk I/O",
+            ChartLine::Churn => "Process churn",
+            ChartLine::Temperature => "CPU temperature",
+            ChartLine::Battery => "Battery",
+        }
+    }
+}
+
+impl ChartSeries {
+    pub fn get_mut(&mut self, line: ChartLine) -> &mut bool {
+        match line {
+            ChartLine::Cpu => &mut self.cpu,
+            ChartLine::Memory => &mut self.memory,
+            ChartLine::DiskIo => &mut self.disk,
+            ChartLine::Churn => &mut self.churn,
+            ChartLine::Temperature => &mut self.temperature,
+            ChartLine::Battery => &mut self.battery,
+        }
+    }
+}
+
+/// The view and sampling settings a named profile switches between.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub refresh_interval_ms: u64,
+    pub refresh_policy: RefreshPolicy,
+    pub sort_mode: SortMode,
+    pub process_filter: String,
+    pub only_current_user: bool,
+    pub columns: ColumnVisibility,
+    pub chart_window_seconds: u32,
+    pub chart_series: ChartSeries,
+    pub chart_network_interface: Option<String>,
+    pub chart_mount_point: Option<String>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            refresh_interval_ms: default_refresh_interval_ms(),
+            refresh_policy: RefreshPolicy::default(),
+            sort_mode: default_sort_mode(),
+            process_filter: String::new(),
+            only_current_user: default_only_current_user(),
+            columns: ColumnVisibility::default(),
+            chart_window_seconds: default_chart_window_seconds(),
+            chart_series: ChartSeries::default(),
+            chart_network_interface: None,
+            chart_mount_point: None,
+        }
+    }
+}
+
+fn default_profiles() -> BTreeMap<String, Profile> {
+    let battery_saver = Profile {
+        refresh_interval_ms: 2000,
+        refresh_policy: RefreshPolicy {
+            hidden_interval_ms: 15_000,
+            idle_interval_ms: 5000,
+            ..RefreshPolicy::default()
+        },
+        chart_window_seconds: 300,
+        ..Profile::default()
+    };
+    let debugging = Profile {
+        refresh_interval_ms: 250,
+        refresh_policy: RefreshPolicy {
+            adaptive: false,
+            ..RefreshPolicy::default()
+        },
+        sort_mode: SortMode::Cpu,
+        only_current_user: false,
+        chart_window_seconds: 60,
+        ..Profile::default()
+    };
+    BTreeMap::from([
+        ("Battery saver".to_string(), battery_saver),
+        ("Debugging".to_string(), debugging),
+    ])
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
+    /// Schema version; files from before versioning count as 0.
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default = "default_sort_mode")]
     pub sort_mode: SortMode,
+    #[serde(default = "default_chart_window_seconds")]
     pub chart_window_seconds: u32,
+    #[serde(default = "default_refresh_interval_ms")]
     pub refresh_interval_ms: u64,
+    #[serde(default = "default_hotkey_enabled")]
     pub hotkey_enabled: bool,
+    #[serde(default)]
+    pub keybindings: KeyBindings,
+    /// Show the process list as a parent/child tree.
+    #[serde(default)]
+    pub tree_view: bool,
+    /// Process list search text.
+    #[serde(default)]
+    pub process_filter: String,
+    #[serde(default)]
+    pub columns: ColumnVisibility,
+    /// Profile the current values were last switched to or saved as.
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    #[serde(default = "default_profiles")]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(default)]
+    pub process_source: ProcessSourceKind,
+    #[serde(default)]
+    pub refresh_policy: RefreshPolicy,
+    /// Footer with memwatch's own memory, CPU, refresh and frame times.
+    #[serde(default = "default_show_status_footer")]
+    pub show_status_footer: bool,
+    #[serde(default)]
+    pub chart_series: ChartSeries,
+    /// Interface whose throughput is drawn in the chart; `None` hides it.
+    #[serde(default)]
+    pub chart_network_interface: Option<String>,
+    /// Mount point whose fill level is drawn in the chart; `None` hides it.
+    #[serde(default)]
+    pub chart_mount_point: Option<String>,
+    /// Filesystems fuller than this percentage are flagged.
+    #[serde(default = "default_disk_alert_percent")]
+    pub disk_alert_percent: f32,
+    /// Hide processes owned by other users in the process list.
+    #[serde(default = "default_only_current_user")]
+    pub only_current_user: bool,
+    #[serde(default)]
+    pub protection: ProtectionRules,
+    #[serde(default)]
+    pub escalation: EscalationRules,
+    #[serde(default)]
+    pub oom_guard: OomGuardSettings,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
-            sort_mode: SortMode::Memory,
-            chart_window_seconds: 120,
-            refresh_interval_ms: 1000,
-            hotkey_enabled: true,
+            version: SETTINGS_VERSION,
+            sort_mode: default_sort_mode(),
+            chart_window_seconds: default_chart_window_seconds(),
+            refresh_interval_ms: default_refresh_interval_ms(),
+            hotkey_enabled: default_hotkey_enabled(),
+            keybindings: KeyBindings::default(),
+            tree_view: false,
+            process_filter: String::new(),
+            columns: ColumnVisibility::default(),
+            active_profile: None,
+            profiles: default_profiles(),
+            process_source: ProcessSourceKind::default(),
+            refresh_policy: RefreshPolicy::default(),
+            show_status_footer: default_show_status_footer(),
+            chart_series: ChartSeries::default(),
+            chart_network_interface: None,
+            chart_mount_point: None,
+            disk_alert_percent: default_disk_alert_percent(),
+            only_current_user: default_only_current_user(),
+            protection: ProtectionRules::default(),
+            escalation: EscalationRules::default(),
+            oom_guard: OomGuardSettings::default(),
         }
     }
 }
 
+fn default_sort_mode() -> SortMode {
+    SortMode::Memory
+}
+
+fn default_chart_window_seconds() -> u32 {
+    120
+}
+
+fn default_refresh_interval_ms() -> u64 {
+    1000
+}
+
+fn default_hotkey_enabled() -> bool {
+    true
+}
+
+fn default_disk_alert_percent() -> f32 {
+    90.0
+}
+
+fn default_only_current_user() -> bool {
+    true
+}
+
+fn default_show_status_footer() -> bool {
+    true
+}
+
 impl Settings {
-    pub fn load() -> Self {
-        if let Some(path) = Self::config_path() {
-            if let Ok(contents) = fs::read_to_string(&path) {
-                if let Ok(settings) = serde_json::from_str(&contents) {
-                    return settings;
-                }
+    /// Loads settings.json, returning any problems found alongside the
+    /// settings instead of silently falling back to defaults. A file with
+    /// problems is copied to settings.json.bak before anything overwrites it.
+    pub fn load() -> (Self, Vec<String>) {
+        let Some(path) = Self::config_path() else {
+            return (Self::default(), Vec::new());
+        };
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return (Self::default(), Vec::new());
+        };
+
+        let (settings, warnings) = Self::from_json(&contents);
+        if !warnings.is_empty() {
+            let _ = fs::copy(&path, path.with_extension("json.bak"));
+        }
+        (settings, warnings)
+    }
+
+    /// `load` for a running instance. A file that cannot be read or is not a
+    /// JSON object, such as one caught mid-write or with a typo, is an error
+    /// rather than a reason to fall back to defaults, so the caller keeps the
+    /// settings it has and the backup is left alone.
+    pub fn reload() -> Result<(Self, Vec<String>), String> {
+        let path = Self::config_path().ok_or("no configuration directory on this system")?;
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("settings.json could not be read ({}); keeping the current settings", e))?;
+        match serde_json::from_str::<Value>(&contents) {
+            Ok(Value::Object(_)) => {}
+            Ok(_) => return Err("settings.json is not a JSON object; keeping the current settings".to_string()),
+            Err(e) => {
+                return Err(format!(
+                    "settings.json could not be parsed ({}); keeping the current settings",
+                    e
+                ))
             }
         }
-        Self::default()
+
+        let (settings, warnings) = Self::from_json(&contents);
+        if !warnings.is_empty() {
+            let _ = fs::copy(&path, path.with_extension("json.bak"));
+        }
+        Ok((settings, warnings))
     }
 
+    /// Parses, migrates and validates a settings file. Fields that fail to
+    /// parse keep their defaults; everything else is preserved.
+    pub fn from_json(contents: &str) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+
+        let mut value: Value = match serde_json::from_str(contents) {
+            Ok(Value::Object(fields)) => Value::Object(fields),
+            Ok(_) => {
+                warnings.push("settings.json is not a JSON object; using defaults".to_string());
+                return (Self::default(), warnings);
+            }
+            Err(e) => {
+                warnings.push(format!("settings.json could not be parsed ({}); using defaults", e));
+                return (Self::default(), warnings);
+            }
+        };
+
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as u32;
+        if version > SETTINGS_VERSION {
+            warnings.push(format!(
+                "settings.json was written by a newer memwatch (schema {}, this build understands {}); unknown fields are ignored and changes will not be saved",
+                version, SETTINGS_VERSION
+            ));
+        } else {
+            migrate(&mut value, version);
+        }
+
+        let mut settings = match serde_json::from_value::<Settings>(value.clone()) {
+            Ok(settings) => settings,
+            Err(_) => Self::from_valid_fields(&value, &mut warnings),
+        };
+        // A newer file keeps its version, which stops `save` from
+        // downgrading it and dropping what this build does not understand.
+        settings.version = version.max(SETTINGS_VERSION);
+        warnings.extend(settings.clamp_to_ranges());
+        warnings.extend(settings.keybindings.validate());
+        (settings, warnings)
+    }
+
+    /// Applies the file's fields one at a time on top of the defaults,
+    /// skipping any that do not parse.
+    fn from_valid_fields(value: &Value, warnings: &mut Vec<String>) -> Self {
+        let mut merged = serde_json::to_value(Self::default()).expect("default settings serialize");
+        if let Value::Object(fields) = value {
+            merge_valid_fields(&mut merged, &mut Vec::new(), fields, warnings);
+        }
+        serde_json::from_value(merged).unwrap_or_default()
+    }
+
+    /// Clamps numeric fields into the ranges the UI offers, returning one
+    /// warning per adjusted value.
+    fn clamp_to_ranges(&mut self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let w = &mut warnings;
+        clamp(w, "chart_window_seconds", &mut self.chart_window_seconds, 60, 300);
+        clamp(w, "refresh_interval_ms", &mut self.refresh_interval_ms, 250, 5000);
+        clamp(w, "disk_alert_percent", &mut self.disk_alert_percent, 50.0, 99.0);
+
+        clamp_refresh_policy(w, "refresh_policy", &mut self.refresh_policy);
+
+        let guard = &mut self.oom_guard;
+        clamp(w, "oom_guard.min_available_mb", &mut guard.min_available_mb, 0, 65_536);
+        clamp(w, "oom_guard.psi_full_limit", &mut guard.psi_full_limit, 0.0, 100.0);
+        clamp(w, "oom_guard.sustain_seconds", &mut guard.sustain_seconds, 1, 600);
+        clamp(w, "oom_guard.cooldown_seconds", &mut guard.cooldown_seconds, 0, 3_600);
+
+        for (name, profile) in &mut self.profiles {
+            let key = format!("profiles.{}.refresh_interval_ms", name);
+            clamp(w, &key, &mut profile.refresh_interval_ms, 250, 5000);
+            let key = format!("profiles.{}.chart_window_seconds", name);
+            clamp(w, &key, &mut profile.chart_window_seconds, 60, 300);
+            clamp_refresh_policy(w, &format!("profiles.{}.refresh_policy", name), &mut profile.refresh_policy);
+        }
+        if self
+            .active_profile
+            .as_ref()
+            .is_some_and(|name| !self.profiles.contains_key(name))
+        {
+            self.active_profile = None;
+        }
+
+        let escalation = &mut self.escalation;
+        for policy in std::iter::once(&mut escalation.default).chain(escalation.overrides.iter_mut()) {
+            for step in &mut policy.steps {
+                let name = format!("escalation wait for `{}`", policy.process_name);
+                clamp(w, &name, &mut step.wait_ms, 0, 120_000);
+            }
+        }
+
+        warnings
+    }
+
+    /// Writes settings.json. Changes made while a profile is active are
+    /// saved into that profile as well.
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.version > SETTINGS_VERSION {
+            return Err(format!(
+                "settings.json is from a newer memwatch (schema {}); not overwriting it",
+                self.version
+            )
+            .into());
+        }
         if let Some(path) = Self::config_path() {
             if let Some(parent) = path.parent() {
                 fs::create_dir_all(parent)?;
             }
-            let contents = serde_json::to_string_pretty(self)?;
+            let mut settings = self.clone();
+            settings.store_active_profile();
+            let contents = serde_json::to_string_pretty(&settings)?;
             fs::write(&path, contents)?;
         }
         Ok(())
     }
 
-    fn config_path() -> Option<PathBuf> {
+    pub fn current_profile(&self) -> Profile {
+        Profile {
+            refresh_interval_ms: self.refresh_interval_ms,
+            refresh_policy: self.refresh_policy.clone(),
+            sort_mode: self.sort_mode,
+            process_filter: self.process_filter.clone(),
+            only_current_user: self.only_current_user,
+            columns: self.columns,
+            chart_window_seconds: self.chart_window_seconds,
+            chart_series: self.chart_series,
+            chart_network_interface: self.chart_network_interface.clone(),
+            chart_mount_point: self.chart_mount_point.clone(),
+        }
+    }
+
+    fn store_active_profile(&mut self) {
+        if let Some(name) = &self.active_profile {
+            let profile = self.current_profile();
+            self.profiles.insert(name.clone(), profile);
+        }
+    }
+
+    /// Keeps the current profile's edits, then applies `name`'s values.
+    pub fn switch_profile(&mut self, name: &str) -> Result<(), String> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            return Err(format!("No profile named \"{}\"", name));
+        };
+        self.store_active_profile();
+
+        self.refresh_interval_ms = profile.refresh_interval_ms;
+        self.refresh_policy = profile.refresh_policy;
+        self.sort_mode = profile.sort_mode;
+        self.process_filter = profile.process_filter;
+        self.only_current_user = profile.only_current_user;
+        self.columns = profile.columns;
+        self.chart_window_seconds = profile.chart_window_seconds;
+        self.chart_series = profile.chart_series;
+        self.chart_network_interface = profile.chart_network_interface;
+        self.chart_mount_point = profile.chart_mount_point;
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Saves the current values under `name` and makes it the active profile.
+    pub fn save_profile_as(&mut self, name: &str) {
+        self.active_profile = Some(name.to_string());
+        self.store_active_profile();
+    }
+
+    pub fn delete_profile(&mut self, name: &str) {
+        self.profiles.remove(name);
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = None;
+        }
+    }
+
+    pub fn config_path() -> Option<PathBuf> {
         directories::ProjectDirs::from("com", "memwatch", "memwatch")
             .map(|dirs| dirs.config_dir().join("settings.json"))
     }
+
+    /// Looks up a field by dotted path, e.g. `refresh_policy.idle_interval_ms`.
+    /// An empty key returns the whole object.
+    pub fn get_field(&self, key: &str) -> Option<Value> {
+        let value = serde_json::to_value(self).ok()?;
+        if key.is_empty() {
+            return Some(value);
+        }
+        key.split('.')
+            .try_fold(&value, |value, part| value.get(part))
+            .cloned()
+    }
+
+    /// Returns a copy with one field replaced, plus warnings for any value
+    /// that had to be clamped. Unknown keys and values of the wrong type are
+    /// errors rather than silently reset.
+    pub fn with_field(&self, key: &str, new_value: Value) -> Result<(Self, Vec<String>), String> {
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        let mut target = &mut value;
+        for part in key.split('.') {
+            target = target
+                .get_mut(part)
+                .filter(|_| !part.is_empty())
+                .ok_or_else(|| format!("Unknown setting `{}`", key))?;
+        }
+        *target = new_value;
+
+        if let Err(e) = serde_json::from_value::<Settings>(value.clone()) {
+            return Err(format!("Invalid value for `{}`: {}", key, e));
+        }
+        Ok(Self::from_json(&value.to_string()))
+    }
+
+    /// True if both would be saved as the same JSON.
+    pub fn same_as(&self, other: &Settings) -> bool {
+        let saved = |settings: &Settings| {
+            let mut settings = settings.clone();
+            settings.store_active_profile();
+            serde_json::to_value(settings).ok()
+        };
+        saved(self) == saved(other)
+    }
+}
+
+/// Polls settings.json so hand edits apply to a running instance. Checks at
+/// most once per second and only compares modification time and size.
+pub struct SettingsWatcher {
+    path: Option<PathBuf>,
+    stamp: Option<(SystemTime, u64)>,
+    /// A changed stamp seen by the last check, reported once it holds for a
+    /// second check so a file still being written is not read.
+    pending: Option<(SystemTime, u64)>,
+    last_check: Instant,
+}
+
+impl SettingsWatcher {
+    pub fn new() -> Self {
+        let path = Settings::config_path();
+        Self {
+            stamp: path.as_deref().and_then(file_stamp),
+            pending: None,
+            path,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// True once after settings.json was created or changed and then left
+    /// alone for a check. A deleted file does not count, so the running
+    /// settings are kept.
+    pub fn poll(&mut self) -> bool {
+        if self.last_check.elapsed() < Duration::from_secs(1) {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        let stamp = self.path.as_deref().and_then(file_stamp);
+        if stamp.is_none() || stamp == self.stamp {
+            self.pending = None;
+            return false;
+        }
+        if stamp != self.pending {
+            self.pending = stamp;
+            return false;
+        }
+        self.stamp = stamp;
+        self.pending = None;
+        true
+    }
+}
+
+fn file_stamp(path: &std::path::Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Moves `value` into `min..=max`, recording a warning if it was outside.
+fn clamp<T: PartialOrd + Copy + std::fmt::Display>(
+    warnings: &mut Vec<String>,
+    name: &str,
+    value: &mut T,
+    min: T,
+    max: T,
+) {
+    let clamped = if *value < min {
+        min
+    } else if *value > max {
+        max
+    } else {
+        return;
+    };
+    warnings.push(format!("`{}` was {}, outside {}..={}; using {}", name, value, min, max, clamped));
+    *value = clamped;
+}
+
+fn clamp_refresh_policy(w: &mut Vec<String>, prefix: &str, policy: &mut RefreshPolicy) {
+    let key = |field: &str| format!("{}.{}", prefix, field);
+    clamp(w, &key("hidden_interval_ms"), &mut policy.hidden_interval_ms, 500, 60_000);
+    clamp(w, &key("idle_interval_ms"), &mut policy.idle_interval_ms, 500, 30_000);
+    clamp(w, &key("idle_cpu_percent"), &mut policy.idle_cpu_percent, 0.0, 100.0);
+    clamp(w, &key("idle_after_seconds"), &mut policy.idle_after_seconds, 1, 600);
+    clamp(w, &key("burst_interval_ms"), &mut policy.burst_interval_ms, 100, 2_000);
+    clamp(w, &key("burst_cpu_delta"), &mut policy.burst_cpu_delta, 1.0, 100.0);
+    clamp(w, &key("burst_memory_delta_mb"), &mut policy.burst_memory_delta_mb, 10.0, 65_536.0);
+    clamp(w, &key("burst_seconds"), &mut policy.burst_seconds, 1, 600);
+}
+
+/// Merges `fields` into the object at `path` in `root` one at a time,
+/// keeping those with which `root` still parses as `Settings`. A nested
+/// object that does not parse as a whole is merged field by field, so one bad
+/// value only resets that value.
+fn merge_valid_fields(root: &mut Value, path: &mut Vec<String>, fields: &Map<String, Value>, warnings: &mut Vec<String>) {
+    let parent: String = path
+        .iter()
+        .map(|key| format!("/{}", key.replace('~', "~0").replace('/', "~1")))
+        .collect();
+    let parses = |root: &Value| serde_json::from_value::<Settings>(root.clone());
+
+    for (key, field) in fields {
+        let previous = object_at(root, &parent).insert(key.clone(), field.clone());
+        let Err(e) = parses(root) else {
+            continue;
+        };
+
+        path.push(key.clone());
+        let mut recursed = false;
+        if let Value::Object(nested) = field {
+            // Start from what was there, or an empty object for a new key
+            // such as a profile, and add the nested fields one by one.
+            let base = match &previous {
+                Some(Value::Object(base)) => base.clone(),
+                _ => Map::new(),
+            };
+            object_at(root, &parent).insert(key.clone(), Value::Object(base));
+            if parses(root).is_ok() {
+                merge_valid_fields(root, path, nested, warnings);
+                recursed = true;
+            }
+        }
+        if !recursed {
+            warnings.push(format!("Ignored invalid `{}` ({}); using the default", path.join("."), e));
+            match previous {
+                Some(previous) => object_at(root, &parent).insert(key.clone(), previous),
+                None => object_at(root, &parent).remove(key),
+            };
+        }
+        path.pop();
+    }
+}
+
+fn object_at<'a>(root: &'a mut Value, pointer: &str) -> &'a mut Map<String, Value> {
+    match root.pointer_mut(pointer) {
+        Some(Value::Object(object)) => object,
+        _ => unreachable!("only objects are merged into"),
+    }
+}
+
+/// Upgrades a settings file from `version` to `SETTINGS_VERSION`, one schema
+/// step at a time.
+fn migrate(value: &mut Value, version: u32) {
+    for from in version..SETTINGS_VERSION {
+        match from {
+            // 0 -> 1: versioning introduced. Fields added before it already
+            // have serde defaults, so only the version itself is new.
+            0 => {}
+            _ => unreachable!("no migration from settings schema {}", from),
+        }
+    }
+    if let Value::Object(fields) = value {
+        fields.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_file_is_migrated_and_keeps_its_values() {
+        let (settings, warnings) = Settings::from_json(
+            r#"{"sort_mode":"Cpu","chart_window_seconds":180,"refresh_interval_ms":750,"hotkey_enabled":false}"#,
+        );
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.sort_mode, SortMode::Cpu);
+        assert_eq!(settings.chart_window_seconds, 180);
+        assert_eq!(settings.refresh_interval_ms, 750);
+        assert!(!settings.hotkey_enabled);
+        assert_eq!(settings.disk_alert_percent, 90.0);
+    }
+
+    #[test]
+    fn watcher_waits_for_the_file_to_settle() {
+        let path = std::env::temp_dir().join(format!("memwatch-watch-{}.json", std::process::id()));
+        fs::write(&path, "{}").unwrap();
+        let mut watcher = SettingsWatcher {
+            stamp: file_stamp(&path),
+            pending: None,
+            path: Some(path.clone()),
+            last_check: Instant::now(),
+        };
+        let poll = |watcher: &mut SettingsWatcher| {
+            watcher.last_check -= Duration::from_secs(2);
+            watcher.poll()
+        };
+
+        fs::write(&path, r#"{"sort_mode":"#).unwrap();
+        assert!(!poll(&mut watcher), "half-written file reported");
+        fs::write(&path, r#"{"sort_mode":"Cpu"}"#).unwrap();
+        assert!(!poll(&mut watcher), "file still changing reported");
+        assert!(poll(&mut watcher));
+        assert!(!poll(&mut watcher));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn invalid_fields_fall_back_individually() {
+        let (settings, warnings) = Settings::from_json(
+            r#"{"version":1,"sort_mode":"Sideways","chart_window_seconds":240,
+                "oom_guard":{"enabled":"yes","allowlist":["postgres"],"min_available_mb":1024},
+                "profiles":{"Work":{"refresh_interval_ms":500,"sort_mode":"Up"}}}"#,
+        );
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings.iter().any(|w| w.contains("`sort_mode`")));
+        assert!(warnings.iter().any(|w| w.contains("`oom_guard.enabled`")));
+        assert!(warnings.iter().any(|w| w.contains("`profiles.Work.sort_mode`")));
+        assert_eq!(settings.sort_mode, SortMode::Memory);
+        assert_eq!(settings.chart_window_seconds, 240);
+        // Only the bad leaf falls back; its siblings are kept.
+        assert!(!settings.oom_guard.enabled);
+        assert_eq!(settings.oom_guard.allowlist, ["postgres"]);
+        assert_eq!(settings.oom_guard.min_available_mb, 1024);
+        assert_eq!(settings.profiles["Work"].refresh_interval_ms, 500);
+        assert!(settings.profiles.contains_key("Debugging"));
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        let (settings, warnings) = Settings::from_json(
+            r#"{"version":1,"refresh_interval_ms":0,"disk_alert_percent":120,"refresh_policy":{"burst_interval_ms":5}}"#,
+        );
+        assert_eq!(settings.refresh_interval_ms, 250);
+        assert_eq!(settings.disk_alert_percent, 99.0);
+        assert_eq!(settings.refresh_policy.burst_interval_ms, 100);
+        // Fields missing from a nested block keep their defaults.
+        assert_eq!(settings.refresh_policy.hidden_interval_ms, 5000);
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+    }
+
+    #[test]
+    fn profiles_keep_their_own_edits() {
+        let mut settings = Settings::default();
+        settings.switch_profile("Debugging").unwrap();
+        assert_eq!(settings.refresh_interval_ms, 250);
+        assert_eq!(settings.sort_mode, SortMode::Cpu);
+
+        settings.process_filter = "cargo".to_string();
+        settings.columns.disk = false;
+        settings.switch_profile("Battery saver").unwrap();
+        assert_eq!(settings.refresh_interval_ms, 2000);
+        assert!(settings.process_filter.is_empty());
+        assert!(settings.columns.disk);
+
+        settings.switch_profile("Debugging").unwrap();
+        assert_eq!(settings.process_filter, "cargo");
+        assert!(!settings.columns.disk);
+        assert!(settings.switch_profile("Missing").is_err());
+    }
+
+    #[test]
+    fn unparseable_and_newer_files_are_reported() {
+        let (settings, warnings) = Settings::from_json("{ not json");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(settings.refresh_interval_ms, 1000);
+
+        let (settings, warnings) =
+            Settings::from_json(r#"{"version":99,"refresh_interval_ms":1500,"future_field":true}"#);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("newer memwatch"));
+        assert_eq!(settings.refresh_interval_ms, 1500);
+        // Saving would downgrade the file and drop `future_field`.
+        assert_eq!(settings.version, 99);
+        assert!(settings.save().is_err());
+    }
 }
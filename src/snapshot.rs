@@ -0,0 +1,183 @@
+use crate::audit;
+use crate::metrics::ProcessInfo;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// A pinned copy of the process list to compare the live state against.
+pub struct Snapshot {
+    pub taken_at: Instant,
+    /// Unix time the snapshot was taken, for exports.
+    timestamp: u64,
+    processes: Vec<ProcessInfo>,
+}
+
+pub struct ProcessDelta {
+    pub pid: u32,
+    pub name: String,
+    pub memory_before_mb: f64,
+    pub memory_after_mb: f64,
+    pub cpu_before: f32,
+    pub cpu_after: f32,
+}
+
+impl ProcessDelta {
+    pub fn memory_delta_mb(&self) -> f64 {
+        self.memory_after_mb - self.memory_before_mb
+    }
+
+    pub fn cpu_delta(&self) -> f32 {
+        self.cpu_after - self.cpu_before
+    }
+}
+
+pub struct SnapshotDiff {
+    pub new: Vec<ProcessInfo>,
+    pub gone: Vec<ProcessInfo>,
+    /// Processes present in both, largest absolute memory change first.
+    pub changed: Vec<ProcessDelta>,
+}
+
+impl Snapshot {
+    pub fn new(processes: &[ProcessInfo]) -> Self {
+        Self {
+            taken_at: Instant::now(),
+            timestamp: audit::unix_now(),
+            processes: processes.to_vec(),
+        }
+    }
+
+    /// Writes the snapshot as JSON to `snapshots/snapshot-<unix time>.json`
+    /// in the data directory and returns the file's path. A second export in
+    /// the same second gets a `-2`, `-3`, ... suffix instead of replacing the
+    /// first.
+    pub fn export(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let dir = directories::ProjectDirs::from("com", "memwatch", "memwatch")
+            .map(|dirs| dirs.data_dir().join("snapshots"))
+            .ok_or("no data directory on this system")?;
+        fs::create_dir_all(&dir)?;
+        let contents = serde_json::json!({
+            "timestamp": self.timestamp,
+            "time": audit::format_timestamp(self.timestamp),
+            "processes": self.processes,
+        });
+        let contents = serde_json::to_string_pretty(&contents)?;
+
+        let mut attempt = 1;
+        loop {
+            let name = match attempt {
+                1 => format!("snapshot-{}.json", self.timestamp),
+                n => format!("snapshot-{}-{}.json", self.timestamp, n),
+            };
+            let path = dir.join(name);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(contents.as_bytes())?;
+                    return Ok(path);
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => attempt += 1,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Processes are matched by PID and name, so a recycled PID shows up as
+    /// one gone and one new process rather than as a change.
+    pub fn diff(&self, live: &[ProcessInfo]) -> SnapshotDiff {
+        let pinned: HashMap<(u32, &str), &ProcessInfo> = self
+            .processes
+            .iter()
+            .map(|p| ((p.pid, p.name.as_str()), p))
+            .collect();
+        let current: HashMap<(u32, &str), &ProcessInfo> =
+            live.iter().map(|p| ((p.pid, p.name.as_str()), p)).collect();
+
+        let new: Vec<ProcessInfo> = live
+            .iter()
+            .filter(|p| !pinned.contains_key(&(p.pid, p.name.as_str())))
+            .cloned()
+            .collect();
+        let gone: Vec<ProcessInfo> = self
+            .processes
+            .iter()
+            .filter(|p| !current.contains_key(&(p.pid, p.name.as_str())))
+            .cloned()
+            .collect();
+
+        let mut changed: Vec<ProcessDelta> = live
+            .iter()
+            .filter_map(|after| {
+                let before = pinned.get(&(after.pid, after.name.as_str()))?;
+                Some(ProcessDelta {
+                    pid: after.pid,
+                    name: after.name.clone(),
+                    memory_before_mb: before.memory_mb,
+                    memory_after_mb: after.memory_mb,
+                    cpu_before: before.cpu_usage,
+                    cpu_after: after.cpu_usage,
+                })
+            })
+            .collect();
+        changed.sort_by(|a, b| {
+            b.memory_delta_mb()
+                .abs()
+                .partial_cmp(&a.memory_delta_mb().abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        SnapshotDiff { new, gone, changed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, memory_mb: f64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            parent: None,
+            cpu_usage: 0.0,
+            memory_mb,
+            disk_read_bps: 0.0,
+            disk_write_bps: 0.0,
+            exe: None,
+            uid: None,
+            user: None,
+            session_id: None,
+            oom_score: None,
+            oom_score_adj: None,
+        }
+    }
+
+    fn pids(processes: &[ProcessInfo]) -> Vec<(u32, &str)> {
+        processes.iter().map(|p| (p.pid, p.name.as_str())).collect()
+    }
+
+    #[test]
+    fn classifies_new_gone_and_changed() {
+        let snapshot = Snapshot::new(&[
+            process(10, "shell", 50.0),
+            process(11, "build", 400.0),
+            process(12, "editor", 300.0),
+            process(13, "cache", 100.0),
+            process(14, "exited", 20.0),
+        ]);
+        let diff = snapshot.diff(&[
+            process(10, "shell", 55.0),
+            process(11, "build", 100.0),
+            process(12, "editor", 350.0),
+            // PID 13 was recycled by an unrelated process.
+            process(13, "worker", 100.0),
+            process(15, "started", 30.0),
+        ]);
+
+        assert_eq!(pids(&diff.new), [(13, "worker"), (15, "started")]);
+        assert_eq!(pids(&diff.gone), [(13, "cache"), (14, "exited")]);
+        let changed: Vec<(u32, f64)> = diff.changed.iter().map(|d| (d.pid, d.memory_delta_mb())).collect();
+        assert_eq!(changed, [(11, -300.0), (12, 50.0), (10, 5.0)]);
+    }
+}
@@ -0,0 +1,204 @@
+// Socket-to-process attribution. Linux only: the inodes behind
+// /proc/<pid>/fd/* are matched against the kernel's socket tables in
+// /proc/net/{tcp,tcp6,udp,udp6}.
+
+use std::net::SocketAddr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketInfo {
+    pub protocol: &'static str,
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    pub state: &'static str,
+}
+
+impl SocketInfo {
+    /// TCP sockets in LISTEN state, and UDP sockets bound without a peer.
+    pub fn is_listening(&self) -> bool {
+        self.state == "LISTEN" || (self.protocol.starts_with("udp") && self.remote.port() == 0)
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn sockets_for_pid(pid: u32) -> Vec<SocketInfo> {
+    use std::collections::HashSet;
+    use std::fs;
+
+    let Ok(fds) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return Vec::new();
+    };
+    let inodes: HashSet<u64> = fds
+        .flatten()
+        .filter_map(|fd| fs::read_link(fd.path()).ok())
+        .filter_map(|target| {
+            target
+                .to_str()?
+                .strip_prefix("socket:[")?
+                .strip_suffix(']')?
+                .parse()
+                .ok()
+        })
+        .collect();
+    if inodes.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sockets = Vec::new();
+    for protocol in ["tcp", "tcp6", "udp", "udp6"] {
+        let Ok(table) = fs::read_to_string(format!("/proc/net/{}", protocol)) else {
+            continue;
+        };
+        for line in table.lines().skip(1) {
+            if let Some((inode, socket)) = parse_table_line(protocol, line) {
+                if inodes.contains(&inode) {
+                    sockets.push(socket);
+                }
+            }
+        }
+    }
+    sockets
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sockets_for_pid(_pid: u32) -> Vec<SocketInfo> {
+    Vec::new()
+}
+
+/// Parses one row of a /proc/net socket table into its inode and socket.
+#[cfg(target_os = "linux")]
+fn parse_table_line(protocol: &'static str, line: &str) -> Option<(u64, SocketInfo)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 {
+        return None;
+    }
+
+    let local = parse_address(fields[1])?;
+    let remote = parse_address(fields[2])?;
+    let state = if protocol.starts_with("tcp") {
+        tcp_state(u8::from_str_radix(fields[3], 16).ok()?)
+    } else {
+        ""
+    };
+    let inode = fields[9].parse().ok()?;
+
+    Some((
+        inode,
+        SocketInfo {
+            protocol,
+            local,
+            remote,
+            state,
+        },
+    ))
+}
+
+/// Decodes `ADDR:PORT` as printed by the kernel: the address is in network
+/// byte order but written as native-endian 32-bit words.
+#[cfg(target_os = "linux")]
+fn parse_address(field: &str) -> Option<SocketAddr> {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    let (address, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let ip = match address.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(
+            u32::from_str_radix(address, 16).ok()?.to_ne_bytes(),
+        )),
+        32 => {
+            let mut octets = [0u8; 16];
+            for (word, chunk) in octets.chunks_mut(4).enumerate() {
+                let value = u32::from_str_radix(&address[word * 8..word * 8 + 8], 16).ok()?;
+                chunk.copy_from_slice(&value.to_ne_bytes());
+            }
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+
+    Some(SocketAddr::new(ip, port))
+}
+
+#[cfg(target_os = "linux")]
+fn tcp_state(code: u8) -> &'static str {
+    match code {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+// The kernel prints addresses as host-endian words, so these fixed lines are
+// what a little-endian machine shows.
+#[cfg(all(test, target_os = "linux", target_endian = "little"))]
+mod tests {
+    use super::*;
+
+    fn parse(protocol: &'static str, line: &str) -> (u64, SocketInfo) {
+        parse_table_line(protocol, line).unwrap()
+    }
+
+    #[test]
+    fn parses_a_listening_ipv4_socket() {
+        let (inode, socket) = parse(
+            "tcp",
+            "   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 12345 1 0000000000000000 100 0 0 10 0",
+        );
+        assert_eq!(inode, 12345);
+        assert_eq!(socket.local, "127.0.0.1:8080".parse().unwrap());
+        assert_eq!(socket.remote, "0.0.0.0:0".parse().unwrap());
+        assert_eq!(socket.state, "LISTEN");
+        assert!(socket.is_listening());
+    }
+
+    #[test]
+    fn parses_an_established_ipv4_socket() {
+        let (inode, socket) = parse(
+            "tcp",
+            "   1: 0F02000A:C350 22D8B85D:01BB 01 00000000:00000000 02:000AFC2B 00000000  1000        0 23456 2 0000000000000000 20 4 30 10 -1",
+        );
+        assert_eq!(inode, 23456);
+        assert_eq!(socket.local, "10.0.2.15:50000".parse().unwrap());
+        assert_eq!(socket.remote, "93.184.216.34:443".parse().unwrap());
+        assert_eq!(socket.state, "ESTABLISHED");
+        assert!(!socket.is_listening());
+    }
+
+    #[test]
+    fn parses_ipv6_words_in_host_order() {
+        let (inode, socket) = parse(
+            "tcp6",
+            "   0: 00000000000000000000000001000000:0016 B80D0120000000000000000001000000:D431 01 00000000:00000000 00:00000000 00000000     0        0 34567 1 0000000000000000 20 4 0 10 -1",
+        );
+        assert_eq!(inode, 34567);
+        assert_eq!(socket.local, "[::1]:22".parse().unwrap());
+        assert_eq!(socket.remote, "[2001:db8::1]:54321".parse().unwrap());
+    }
+
+    #[test]
+    fn unconnected_udp_counts_as_listening() {
+        let (_, socket) = parse(
+            "udp",
+            "  100: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 45678 2 0000000000000000 0",
+        );
+        assert_eq!(socket.local, "127.0.0.53:53".parse().unwrap());
+        assert_eq!(socket.state, "");
+        assert!(socket.is_listening());
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert!(parse_table_line("tcp", "  sl  local_address rem_address   st").is_none());
+        assert!(parse_table_line("tcp", "   0: 0100007F 00000000:0000 0A 0 0 0 0 0 1").is_none());
+        assert_eq!(parse_address("0100:0050"), None);
+    }
+}
@@ -0,0 +1,354 @@
+// Where MetricsCollector gets its process and system samples from: sysinfo,
+// or the native /proc reader on Linux. Tests drive the collector with a
+// scripted fake and a manual clock.
+
+use crate::settings::ProcessSourceKind;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users};
+
+/// One process as reported by a source.
+#[derive(Clone, Debug, Default)]
+pub struct SourceProcess {
+    pub pid: u32,
+    pub name: String,
+    pub parent: Option<u32>,
+    pub cpu_usage: f32,
+    pub memory_bytes: u64,
+    /// Bytes read and written since the previous refresh.
+    pub disk_read_bytes: u64,
+    pub disk_written_bytes: u64,
+    pub exe: Option<String>,
+    pub uid: Option<u32>,
+    pub user: Option<String>,
+    pub session_id: Option<u32>,
+    pub oom_score: Option<i32>,
+    pub oom_score_adj: Option<i32>,
+}
+
+/// System-wide counters from the latest refresh.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemSample {
+    /// Average over all cores, in percent.
+    pub cpu_usage: f32,
+    pub cpu_count: usize,
+    pub total_memory: u64,
+    pub used_memory: u64,
+    pub available_memory: u64,
+}
+
+/// What the latest refresh cost the source itself.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SamplingCost {
+    pub duration: Duration,
+    pub processes_scanned: usize,
+    /// Files opened during the refresh, for backends that count them.
+    pub files_read: Option<usize>,
+}
+
+pub trait ProcessSource {
+    /// Takes a new sample; called once per collector refresh.
+    fn refresh(&mut self);
+    fn processes(&self) -> &[SourceProcess];
+    fn system(&self) -> SystemSample;
+    fn cost(&self) -> SamplingCost;
+    /// Makes the next refresh read `pid`'s OOM scores again, e.g. after
+    /// memwatch changed them, instead of keeping cached ones.
+    fn invalidate_oom_scores(&mut self, _pid: u32) {}
+}
+
+/// Builds the backend selected in Settings. The /proc backend only exists on
+/// Linux; elsewhere sysinfo is used regardless.
+pub fn create(kind: ProcessSourceKind) -> Box<dyn ProcessSource> {
+    match kind {
+        #[cfg(target_os = "linux")]
+        ProcessSourceKind::Procfs => Box::new(crate::procfs::ProcfsSource::new()),
+        _ => Box::new(SysinfoSource::new()),
+    }
+}
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// OOM scores only move with memory pressure, so they are re-read every this
+/// many refreshes rather than on each one.
+pub const OOM_REFRESH_TICKS: u64 = 10;
+
+/// How long a UID without an account stays unresolved before the account
+/// list is read again for it.
+const UNKNOWN_UID_RETRY: Duration = Duration::from_secs(60);
+
+/// UIDs the last account list read could not name, common in containers,
+/// so that processes running as them do not make every refresh read the
+/// list again.
+pub struct UnresolvedUids {
+    uids: HashSet<u32>,
+    listed: Instant,
+}
+
+impl UnresolvedUids {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            uids: HashSet::new(),
+            listed: now,
+        }
+    }
+
+    /// Whether `uid`, which has no account in the current list, is worth
+    /// reading the list again for: it is new, or the last read is a minute
+    /// old.
+    pub fn worth_reload(&self, uid: u32, now: Instant) -> bool {
+        !self.uids.contains(&uid) || now.duration_since(self.listed) >= UNKNOWN_UID_RETRY
+    }
+
+    /// Records a read of the list at `now` and the UIDs it left unnamed.
+    pub fn reloaded(&mut self, unresolved: impl IntoIterator<Item = u32>, now: Instant) {
+        self.uids = unresolved.into_iter().collect();
+        self.listed = now;
+    }
+}
+
+pub struct SysinfoSource {
+    system: System,
+    users: Users,
+    unresolved_uids: UnresolvedUids,
+    processes: Vec<SourceProcess>,
+    /// `oom_score` and `oom_score_adj` per PID from the last read.
+    oom_scores: HashMap<u32, (Option<i32>, Option<i32>)>,
+    ticks: u64,
+    cost: SamplingCost,
+}
+
+impl SysinfoSource {
+    pub fn new() -> Self {
+        let mut system = System::new_all();
+        system.refresh_all();
+
+        let mut source = Self {
+            system,
+            users: Users::new_with_refreshed_list(),
+            unresolved_uids: UnresolvedUids::new(Instant::now()),
+            processes: Vec::new(),
+            oom_scores: HashMap::new(),
+            ticks: 0,
+            cost: SamplingCost::default(),
+        };
+        source.collect_processes();
+        source
+    }
+
+    fn collect_processes(&mut self) {
+        // Re-read the account list only when a process runs as an unknown UID
+        // that the previous read did not already fail to resolve.
+        let now = Instant::now();
+        let is_unknown = |users: &Users, uid: &sysinfo::Uid| users.get_user_by_id(uid).is_none();
+        let unknown_user = self.system.processes().values().any(|process| {
+            process.user_id().is_some_and(|uid| {
+                is_unknown(&self.users, uid) && self.unresolved_uids.worth_reload(**uid, now)
+            })
+        });
+        if unknown_user {
+            self.users.refresh_list();
+            let unresolved = self
+                .system
+                .processes()
+                .values()
+                .filter_map(|process| process.user_id())
+                .filter(|uid| is_unknown(&self.users, uid))
+                .map(|uid| **uid);
+            self.unresolved_uids.reloaded(unresolved, now);
+        }
+
+        // Scores are read for new PIDs every tick and for everyone else only
+        // every OOM_REFRESH_TICKS.
+        let reread_scores = self.ticks.is_multiple_of(OOM_REFRESH_TICKS);
+        self.ticks += 1;
+        let processes = self.system.processes();
+        self.oom_scores.retain(|pid, _| processes.contains_key(&sysinfo::Pid::from_u32(*pid)));
+        let oom_scores = &mut self.oom_scores;
+
+        self.processes = processes
+            .iter()
+            .map(|(pid, process)| {
+                let disk_usage = process.disk_usage();
+                if reread_scores || !oom_scores.contains_key(&pid.as_u32()) {
+                    let scores = (
+                        read_proc_value(pid.as_u32(), "oom_score"),
+                        read_proc_value(pid.as_u32(), "oom_score_adj"),
+                    );
+                    oom_scores.insert(pid.as_u32(), scores);
+                }
+                let (oom_score, oom_score_adj) = oom_scores[&pid.as_u32()];
+                SourceProcess {
+                    pid: pid.as_u32(),
+                    name: process.name().to_string_lossy().to_string(),
+                    parent: process.parent().map(|parent| parent.as_u32()),
+                    cpu_usage: process.cpu_usage(),
+                    memory_bytes: process.memory(),
+                    disk_read_bytes: disk_usage.read_bytes,
+                    disk_written_bytes: disk_usage.written_bytes,
+                    exe: process.exe().map(|path| path.to_string_lossy().to_string()),
+                    uid: process.user_id().map(|uid| **uid),
+                    user: process
+                        .user_id()
+                        .and_then(|uid| self.users.get_user_by_id(uid))
+                        .map(|user| user.name().to_string()),
+                    session_id: read_session_id(pid.as_u32()),
+                    oom_score,
+                    oom_score_adj,
+                }
+            })
+            .collect();
+    }
+}
+
+impl ProcessSource for SysinfoSource {
+    fn refresh(&mut self) {
+        let started = Instant::now();
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new()
+                .with_cpu()
+                .with_memory()
+                .with_disk_usage()
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_user(UpdateKind::OnlyIfNotSet),
+        );
+        self.system.refresh_cpu_all();
+        self.system.refresh_memory();
+        self.collect_processes();
+        self.cost = SamplingCost {
+            duration: started.elapsed(),
+            processes_scanned: self.processes.len(),
+            files_read: None,
+        };
+    }
+
+    fn processes(&self) -> &[SourceProcess] {
+        &self.processes
+    }
+
+    fn system(&self) -> SystemSample {
+        let cpus = self.system.cpus();
+        SystemSample {
+            cpu_usage: cpus.iter().map(|cpu| cpu.cpu_usage()).sum::<f32>() / cpus.len().max(1) as f32,
+            cpu_count: cpus.len(),
+            total_memory: self.system.total_memory(),
+            used_memory: self.system.used_memory(),
+            available_memory: self.system.available_memory(),
+        }
+    }
+
+    fn cost(&self) -> SamplingCost {
+        self.cost
+    }
+
+    fn invalidate_oom_scores(&mut self, pid: u32) {
+        self.oom_scores.remove(&pid);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_proc_value(pid: u32, file: &str) -> Option<i32> {
+    std::fs::read_to_string(format!("/proc/{}/{}", pid, file))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_proc_value(_pid: u32, _file: &str) -> Option<i32> {
+    None
+}
+
+#[cfg(unix)]
+fn read_session_id(pid: u32) -> Option<u32> {
+    let sid = unsafe { libc::getsid(pid as libc::pid_t) };
+    (sid >= 0).then_some(sid as u32)
+}
+
+#[cfg(not(unix))]
+fn read_session_id(_pid: u32) -> Option<u32> {
+    None
+}
+
+#[cfg(test)]
+pub mod fake {
+    use super::*;
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    /// Replays a fixed list of samples, one per refresh; the last one repeats
+    /// once the script runs out.
+    pub struct ScriptedSource {
+        script: VecDeque<(SystemSample, Vec<SourceProcess>)>,
+        current: (SystemSample, Vec<SourceProcess>),
+    }
+
+    impl ScriptedSource {
+        pub fn new(script: Vec<(SystemSample, Vec<SourceProcess>)>) -> Self {
+            let mut script: VecDeque<_> = script.into();
+            let current = script.pop_front().unwrap_or_default();
+            Self { script, current }
+        }
+    }
+
+    impl ProcessSource for ScriptedSource {
+        fn refresh(&mut self) {
+            if let Some(next) = self.script.pop_front() {
+                self.current = next;
+            }
+        }
+
+        fn processes(&self) -> &[SourceProcess] {
+            &self.current.1
+        }
+
+        fn system(&self) -> SystemSample {
+            self.current.0
+        }
+
+        fn cost(&self) -> SamplingCost {
+            SamplingCost {
+                processes_scanned: self.current.1.len(),
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Clock that only moves when told to. Clones share the same time.
+    #[derive(Clone)]
+    pub struct ManualClock {
+        now: Rc<Cell<Instant>>,
+    }
+
+    impl ManualClock {
+        pub fn new() -> Self {
+            Self {
+                now: Rc::new(Cell::new(Instant::now())),
+            }
+        }
+
+        pub fn advance(&self, by: Duration) {
+            self.now.set(self.now.get() + by);
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            self.now.get()
+        }
+    }
+}
@@ -1,26 +1,99 @@
+use crate::actions::{self, Action};
+use crate::audit::{self, AuditEntry, KillOrigin};
+use crate::escalation::{EscalationPolicy, EscalationStep, GracefulSignal};
 use crate::hotkey::HotkeyManager;
 use crate::ipc::IpcServer;
-use crate::killer::{force_kill_process, terminate_process, KillStatus};
+use crate::keybindings::{KeyAction, KeyBinding, KeyBindings};
+use crate::killer::{
+    continue_process, force_kill_process, force_kill_processes, stop_process, terminate_process, terminate_processes,
+    KillContext, KillStatus,
+};
 use crate::metrics::{MetricsCollector, ProcessInfo};
-use crate::settings::{Settings, SortMode};
+use crate::oom_guard::{GuardAction, OomGuard, VictimPolicy};
+use crate::palette::{self, CommandPalette, PaletteItem};
+#[cfg(target_os = "linux")]
+use crate::priority::{set_affinity, set_io_priority, set_oom_score_adj, IoPriorityClass};
+use crate::priority::{get_nice, set_nice, PriorityStatus};
+use crate::protection::ProtectionRules;
+use crate::proc_events::ProcessEventKind;
+use crate::scheduler::{RefreshMode, RefreshScheduler};
+use crate::self_monitor::{SelfMonitor, SelfSample};
+use crate::settings::{ChartLine, ProcessSourceKind, Settings, SettingsWatcher, SortMode};
+use crate::snapshot::{Snapshot, SnapshotDiff};
+use crate::users;
+use crate::sockets::{sockets_for_pid, SocketInfo};
 use eframe::egui;
 use egui_plot::{Line, Plot, PlotPoints};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
 use std::time::{Duration, Instant};
 
 pub struct MemwatchApp {
     metrics: MetricsCollector,
+    active_view: View,
+    pinned_snapshot: Option<Snapshot>,
+    self_monitor: SelfMonitor,
+    /// Problems found in settings.json at startup, shown until dismissed.
+    settings_warnings: Vec<String>,
+    settings_watcher: SettingsWatcher,
+    scheduler: RefreshScheduler,
+    /// Interval and mode chosen after the latest refresh.
+    refresh_interval: (Duration, RefreshMode),
+    current_uid: Option<u32>,
+    snapshot_diff: Option<SnapshotDiff>,
     processes: Vec<ProcessInfo>,
+    visible_processes: Vec<ProcessInfo>,
+    /// Indentation of each visible process in tree view; empty otherwise.
+    visible_depths: Vec<usize>,
     settings: Settings,
     last_refresh: Instant,
-    search_filter: String,
+    new_profile_name: String,
+    keymap: Vec<(KeyAction, KeyBinding)>,
+    /// Per-action binding text being edited in Settings.
+    keybinding_text: Vec<String>,
+    focus_filter: bool,
+    /// Focus the default button of the next dialog, for keyboard use.
+    focus_dialog: bool,
+    scroll_to_row: Option<usize>,
+    /// Rows that fit in the process table, for paging.
+    page_rows: usize,
+    show_signal_menu: bool,
+    palette: Option<CommandPalette>,
     kill_confirmation: Option<u32>,
+    /// PIDs whose signals are still being sent on a worker thread.
+    signalling: HashSet<u32>,
+    kill_sender: Sender<KillOutcome>,
+    kill_results: Receiver<KillOutcome>,
+    selected: HashSet<u32>,
+    selection_anchor: Option<u32>,
+    pending_batch: Option<BatchAction>,
+    priority_editor: Option<PriorityEditor>,
+    allow_protected_kills: bool,
+    protection_text: [String; 4],
+    oom_allowlist_text: String,
     notification: Option<(String, Instant, NotificationLevel)>,
     show_settings: bool,
+    show_history: bool,
+    show_exited: bool,
+    details: Option<(u32, Vec<SocketInfo>)>,
+    history: Vec<AuditEntry>,
+    oom_guard: OomGuard,
+    full_filesystems: HashSet<String>,
     hotkey_manager: Option<HotkeyManager>,
     ipc_server: Option<IpcServer>,
+    /// For waking the UI from worker threads.
+    egui_ctx: egui::Context,
     window_visible: bool,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum View {
+    Processes,
+    Changes,
+    Users,
+}
+
 #[derive(Clone, Copy)]
 enum NotificationLevel {
     Info,
@@ -28,31 +101,196 @@ enum NotificationLevel {
     Error,
 }
 
+/// Editing state for the row context menu; reset whenever the menu is opened
+/// on a different process.
+struct PriorityEditor {
+    pid: u32,
+    nice: i32,
+    #[cfg(target_os = "linux")]
+    cpus: Vec<bool>,
+    #[cfg(target_os = "linux")]
+    io_class: IoPriorityClass,
+    #[cfg(target_os = "linux")]
+    io_level: u8,
+    #[cfg(target_os = "linux")]
+    oom_score_adj: i32,
+}
+
+/// Result of signals sent on a worker thread, picked up by `update`.
+enum KillOutcome {
+    Terminate(u32, KillStatus),
+    ForceKill(u32, KillStatus),
+    Batch(BatchAction, Vec<(u32, KillStatus)>),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BatchAction {
+    Terminate,
+    ForceKill,
+    Stop,
+    Continue,
+}
+
+impl BatchAction {
+    fn label(self) -> &'static str {
+        match self {
+            BatchAction::Terminate => "Terminate",
+            BatchAction::ForceKill => "Force Kill",
+            BatchAction::Stop => "Stop (SIGSTOP)",
+            BatchAction::Continue => "Continue (SIGCONT)",
+        }
+    }
+}
+
 impl MemwatchApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let mut metrics = MetricsCollector::new();
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let (settings, settings_warnings) = Settings::load();
+        let mut metrics = MetricsCollector::new(settings.process_source);
         metrics.refresh();
         let processes = metrics.get_processes();
-        let settings = Settings::load();
 
-        let hotkey_manager = HotkeyManager::new();
+        let hotkey_manager = HotkeyManager::new(&cc.egui_ctx);
         let ipc_server = IpcServer::new();
 
-        Self {
+        let protection_text = protection_lists_to_text(&settings.protection);
+        let oom_allowlist_text = settings.oom_guard.allowlist.join(", ");
+        let (keymap, _) = settings.keybindings.keymap();
+        let keybinding_text = keybindings_to_text(&settings.keybindings);
+        let (kill_sender, kill_results) = mpsc::channel();
+
+        let mut app = Self {
             metrics,
+            active_view: View::Processes,
+            pinned_snapshot: None,
+            self_monitor: SelfMonitor::new(),
+            settings_warnings,
+            settings_watcher: SettingsWatcher::new(),
+            scheduler: RefreshScheduler::default(),
+            refresh_interval: (
+                Duration::from_millis(settings.refresh_interval_ms),
+                RefreshMode::Normal,
+            ),
+            current_uid: users::current_uid(),
+            snapshot_diff: None,
             processes,
+            visible_processes: Vec::new(),
+            visible_depths: Vec::new(),
             settings,
             last_refresh: Instant::now(),
-            search_filter: String::new(),
+            new_profile_name: String::new(),
+            keymap,
+            keybinding_text,
+            focus_filter: false,
+            focus_dialog: false,
+            scroll_to_row: None,
+            page_rows: 20,
+            show_signal_menu: false,
+            palette: None,
             kill_confirmation: None,
+            signalling: HashSet::new(),
+            kill_sender,
+            kill_results,
+            selected: HashSet::new(),
+            selection_anchor: None,
+            pending_batch: None,
+            priority_editor: None,
+            allow_protected_kills: false,
+            protection_text,
+            oom_allowlist_text,
             notification: None,
             show_settings: false,
+            show_history: false,
+            show_exited: false,
+            details: None,
+            history: Vec::new(),
+            oom_guard: OomGuard::new(),
+            full_filesystems: HashSet::new(),
             hotkey_manager,
             ipc_server,
+            egui_ctx: cc.egui_ctx.clone(),
             window_visible: true,
+        };
+        app.rebuild_visible_processes();
+        app.sync_global_hotkey();
+        app
+    }
+
+    /// Recomputes the filtered and sorted view of `processes`. Called only when
+    /// the underlying data, the filter or the sort mode changes.
+    fn rebuild_visible_processes(&mut self) {
+        let mut visible = self.processes.clone();
+
+        if self.settings.only_current_user {
+            if let Some(uid) = self.current_uid {
+                visible.retain(|p| p.uid == Some(uid));
+            }
+        }
+
+        if !self.settings.process_filter.is_empty() {
+            let filter_lower = self.settings.process_filter.to_lowercase();
+            visible.retain(|p| {
+                p.name.to_lowercase().contains(&filter_lower)
+                    || p.pid.to_string().contains(&filter_lower)
+            });
+        }
+
+        match self.settings.sort_mode {
+            SortMode::Memory => {
+                visible.sort_by(|a, b| {
+                    b.memory_mb
+                        .partial_cmp(&a.memory_mb)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+            }
+            SortMode::Cpu => {
+                visible.sort_by(|a, b| {
+                    b.cpu_usage
+                        .partial_cmp(&a.cpu_usage)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+            }
+            SortMode::OomScore => {
+                visible.sort_by_key(|p| std::cmp::Reverse(p.oom_score));
+            }
+            SortMode::DiskIo => {
+                visible.sort_by(|a, b| {
+                    (b.disk_read_bps + b.disk_write_bps)
+                        .partial_cmp(&(a.disk_read_bps + a.disk_write_bps))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+            }
+        }
+
+        if self.settings.tree_view {
+            let (visible, depths) = tree_order(visible);
+            self.visible_processes = visible;
+            self.visible_depths = depths;
+        } else {
+            self.visible_processes = visible;
+            self.visible_depths.clear();
+        }
+    }
+
+    fn set_sort_mode(&mut self, mode: SortMode) {
+        self.settings.sort_mode = mode;
+        let _ = self.settings.save();
+        self.rebuild_visible_processes();
+    }
+
+    /// Rules passed to `killer`; `None` once the user has opted out of protection.
+    fn kill_guard(&self) -> Option<&ProtectionRules> {
+        if self.allow_protected_kills {
+            None
+        } else {
+            Some(&self.settings.protection)
         }
     }
 
+    fn protection_reason(&self, process: &ProcessInfo) -> Option<String> {
+        self.kill_guard()?
+            .reason(process.pid, &process.name, process.exe.as_deref(), process.uid)
+    }
+
     fn show_notification(&mut self, message: String, level: NotificationLevel) {
         self.notification = Some((message, Instant::now(), level));
     }
@@ -87,232 +325,1029 @@ impl MemwatchApp {
         ui.horizontal(|ui| {
             ui.label("Sort by:");
             if ui.selectable_label(self.settings.sort_mode == SortMode::Memory, "Memory").clicked() {
-                self.settings.sort_mode = SortMode::Memory;
-                let _ = self.settings.save();
+                self.set_sort_mode(SortMode::Memory);
             }
             if ui.selectable_label(self.settings.sort_mode == SortMode::Cpu, "CPU").clicked() {
-                self.settings.sort_mode = SortMode::Cpu;
-                let _ = self.settings.save();
+                self.set_sort_mode(SortMode::Cpu);
+            }
+            if ui.selectable_label(self.settings.sort_mode == SortMode::DiskIo, "Disk I/O").clicked() {
+                self.set_sort_mode(SortMode::DiskIo);
+            }
+            if cfg!(target_os = "linux")
+                && ui.selectable_label(self.settings.sort_mode == SortMode::OomScore, "OOM Score").clicked()
+            {
+                self.set_sort_mode(SortMode::OomScore);
             }
 
             ui.separator();
 
             ui.label("Filter:");
-            ui.text_edit_singleline(&mut self.search_filter);
+            let filter = ui.text_edit_singleline(&mut self.settings.process_filter);
+            if std::mem::take(&mut self.focus_filter) {
+                filter.request_focus();
+            }
+            if filter.changed() {
+                self.rebuild_visible_processes();
+            }
+            if filter.lost_focus() {
+                let _ = self.settings.save();
+            }
+            ui.menu_button("Columns", |ui| {
+                let columns = &mut self.settings.columns;
+                let mut changed = false;
+                changed |= ui.checkbox(&mut columns.pid, "PID").changed();
+                changed |= ui.checkbox(&mut columns.cpu, "CPU %").changed();
+                changed |= ui.checkbox(&mut columns.memory, "Memory").changed();
+                changed |= ui.checkbox(&mut columns.disk, "Disk R / W").changed();
+                if cfg!(target_os = "linux") {
+                    changed |= ui.checkbox(&mut columns.oom, "OOM Score / Adj").changed();
+                }
+                if changed {
+                    let _ = self.settings.save();
+                }
+            });
+            if self.current_uid.is_some()
+                && ui
+                    .checkbox(&mut self.settings.only_current_user, "Only mine")
+                    .on_hover_text("Hide processes owned by other users")
+                    .changed()
+            {
+                let _ = self.settings.save();
+                self.rebuild_visible_processes();
+            }
+            if ui.checkbox(&mut self.settings.tree_view, "Tree").changed() {
+                let _ = self.settings.save();
+                self.rebuild_visible_processes();
+            }
+            if ui.button("Select All").on_hover_text("Select every process matching the filter").clicked() {
+                self.selected = self.visible_processes.iter().map(|p| p.pid).collect();
+            }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui.button("⚙ Settings").clicked() {
-                    self.show_settings = !self.show_settings;
-                }
+                ui.label(format!(
+                    "{} of {} processes",
+                    self.visible_processes.len(),
+                    self.processes.len()
+                ));
             });
         });
+
+        if !self.selected.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} selected", self.selected.len()));
+                for action in [
+                    BatchAction::Terminate,
+                    BatchAction::ForceKill,
+                    BatchAction::Stop,
+                    BatchAction::Continue,
+                ] {
+                    if ui.button(action.label()).clicked() {
+                        self.pending_batch = Some(action);
+                    }
+                }
+                if ui.button("Clear Selection").clicked() {
+                    self.selected.clear();
+                    self.selection_anchor = None;
+                }
+            });
+        }
     }
 
-    fn render_process_list(&mut self, ui: &mut egui::Ui) {
-        let mut sorted_processes = self.processes.clone();
+    fn handle_row_click(&mut self, index: usize, modifiers: egui::Modifiers) {
+        let pid = self.visible_processes[index].pid;
 
-        if !self.search_filter.is_empty() {
-            let filter_lower = self.search_filter.to_lowercase();
-            sorted_processes.retain(|p| {
-                p.name.to_lowercase().contains(&filter_lower)
-                    || p.pid.to_string().contains(&filter_lower)
-            });
+        let anchor_index = self
+            .selection_anchor
+            .and_then(|anchor| self.visible_processes.iter().position(|p| p.pid == anchor));
+
+        if let (true, Some(anchor_index)) = (modifiers.shift, anchor_index) {
+            if !modifiers.command {
+                self.selected.clear();
+            }
+            let (lo, hi) = (anchor_index.min(index), anchor_index.max(index));
+            self.selected
+                .extend(self.visible_processes[lo..=hi].iter().map(|p| p.pid));
+            return;
         }
 
-        match self.settings.sort_mode {
-            SortMode::Memory => {
-                sorted_processes.sort_by(|a, b| {
-                    b.memory_mb
-                        .partial_cmp(&a.memory_mb)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                });
+        if modifiers.command {
+            if !self.selected.remove(&pid) {
+                self.selected.insert(pid);
             }
-            SortMode::Cpu => {
-                sorted_processes.sort_by(|a, b| {
-                    b.cpu_usage
-                        .partial_cmp(&a.cpu_usage)
-                        .unwrap_or(std::cmp::Ordering::Equal)
+        } else {
+            self.selected.clear();
+            self.selected.insert(pid);
+        }
+        self.selection_anchor = Some(pid);
+    }
+
+    fn render_batch_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(action) = self.pending_batch else {
+            return;
+        };
+
+        let targets: Vec<ProcessInfo> = self
+            .processes
+            .iter()
+            .filter(|p| self.selected.contains(&p.pid))
+            .cloned()
+            .collect();
+
+        let focus_confirm = std::mem::take(&mut self.focus_dialog);
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new(format!("Confirm {}", action.label()))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} will be applied to {} process(es):",
+                    action.label(),
+                    targets.len()
+                ));
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for process in &targets {
+                        match self.protection_reason(process) {
+                            Some(reason) => {
+                                ui.label(format!("{} ({}) — skipped: {}", process.name, process.pid, reason));
+                            }
+                            None => {
+                                ui.label(format!("{} ({})", process.name, process.pid));
+                            }
+                        }
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let confirm = ui.button("Confirm");
+                    if focus_confirm {
+                        confirm.request_focus();
+                    }
+                    if confirm.clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
                 });
+            });
+
+        if confirmed {
+            let pids: Vec<u32> = targets
+                .iter()
+                .map(|p| p.pid)
+                .filter(|pid| !self.signalling.contains(pid))
+                .collect();
+            self.run_batch(action, pids);
+            self.pending_batch = None;
+        } else if cancelled {
+            self.pending_batch = None;
+        }
+    }
+
+    /// Signals every PID on a worker thread; `report_batch` summarises the
+    /// results once they arrive.
+    fn run_batch(&mut self, action: BatchAction, pids: Vec<u32>) {
+        self.signalling.extend(&pids);
+        self.spawn_kill(KillOrigin::Gui, move |ctx| {
+            let results: Vec<(u32, KillStatus)> = match action {
+                BatchAction::Terminate => terminate_processes(&pids, ctx),
+                BatchAction::ForceKill => force_kill_processes(&pids, ctx),
+                BatchAction::Stop => pids.iter().map(|&pid| (pid, stop_process(pid, ctx))).collect(),
+                BatchAction::Continue => pids.iter().map(|&pid| (pid, continue_process(pid, ctx))).collect(),
+            };
+            KillOutcome::Batch(action, results)
+        });
+    }
+
+    fn report_batch(&mut self, action: BatchAction, results: Vec<(u32, KillStatus)>) {
+        for (pid, _) in &results {
+            self.signalling.remove(pid);
+        }
+
+        let mut succeeded = 0;
+        let mut still_running = 0;
+        let mut not_found = 0;
+        let mut refused = 0;
+        let mut failures: Vec<String> = Vec::new();
+        for (pid, status) in &results {
+            match status {
+                KillStatus::Success => succeeded += 1,
+                KillStatus::RequiresConfirmation(_) => still_running += 1,
+                KillStatus::NotFound => not_found += 1,
+                KillStatus::Refused(_) => refused += 1,
+                KillStatus::Failed(err) => failures.push(format!("{}: {}", pid, err)),
             }
         }
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            use egui_extras::{Column, TableBuilder};
+        // Keep only the processes that still need attention selected, so a
+        // follow-up Force Kill applies to exactly those.
+        self.selected = results
+            .iter()
+            .filter(|(_, status)| {
+                matches!(status, KillStatus::RequiresConfirmation(_) | KillStatus::Failed(_))
+            })
+            .map(|(pid, _)| *pid)
+            .collect();
 
-            TableBuilder::new(ui)
-                .striped(true)
-                .resizable(true)
-                .column(Column::auto().at_least(250.0))
-                .column(Column::auto().at_least(80.0))
-                .column(Column::auto().at_least(100.0))
-                .column(Column::auto().at_least(100.0))
-                .column(Column::auto().at_least(100.0))
-                .header(20.0, |mut header| {
-                    header.col(|ui| {
-                        ui.strong("Process Name");
-                    });
+        let mut parts = vec![format!("{} succeeded", succeeded)];
+        if still_running > 0 {
+            parts.push(format!("{} still running", still_running));
+        }
+        if not_found > 0 {
+            parts.push(format!("{} not found", not_found));
+        }
+        if refused > 0 {
+            parts.push(format!("{} refused (protected)", refused));
+        }
+        if !failures.is_empty() {
+            parts.push(format!("{} failed ({})", failures.len(), failures.join("; ")));
+        }
+
+        let level = if !failures.is_empty() {
+            NotificationLevel::Error
+        } else if still_running > 0 || refused > 0 {
+            NotificationLevel::Info
+        } else {
+            NotificationLevel::Success
+        };
+        self.show_notification(format!("{}: {}", action.label(), parts.join(", ")), level);
+    }
+
+    /// Keyboard-reachable list of the batch actions for the selection.
+    fn render_signal_menu(&mut self, ctx: &egui::Context) {
+        if !self.show_signal_menu {
+            return;
+        }
+
+        let focus_first = std::mem::take(&mut self.focus_dialog);
+        let mut chosen = None;
+        egui::Window::new("Send signal")
+            .open(&mut self.show_signal_menu)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!("{} selected process(es)", self.selected.len()));
+                for (i, action) in [
+                    BatchAction::Terminate,
+                    BatchAction::ForceKill,
+                    BatchAction::Stop,
+                    BatchAction::Continue,
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    let button = ui.button(action.label());
+                    if focus_first && i == 0 {
+                        button.request_focus();
+                    }
+                    if button.clicked() {
+                        chosen = Some(action);
+                    }
+                }
+                ui.label("Tab to move, Enter to choose, Esc to cancel");
+            });
+
+        if let Some(action) = chosen {
+            self.show_signal_menu = false;
+            self.pending_batch = Some(action);
+            self.focus_dialog = true;
+        }
+    }
+
+    fn render_process_list(&mut self, ui: &mut egui::Ui) {
+        use egui_extras::{Column, TableBuilder};
+
+        let modifiers = ui.input(|i| i.modifiers);
+        let columns = self.settings.columns;
+        let show_oom = columns.oom && cfg!(target_os = "linux");
+        self.page_rows = ((ui.available_height() - 20.0) / 18.0).max(1.0) as usize;
+
+        let mut table = TableBuilder::new(ui);
+        if let Some(row) = self.scroll_to_row.take() {
+            table = table.scroll_to_row(row, None);
+        }
+        table
+            .striped(true)
+            .resizable(true)
+            .sense(egui::Sense::click())
+            .column(Column::auto().at_least(250.0))
+            .columns(Column::auto().at_least(80.0), columns.pid as usize)
+            .columns(Column::auto().at_least(100.0), columns.cpu as usize)
+            .columns(Column::auto().at_least(100.0), columns.memory as usize)
+            .columns(Column::auto().at_least(120.0), columns.disk as usize)
+            .columns(Column::auto().at_least(70.0), if show_oom { 2 } else { 0 })
+            .column(Column::remainder().at_least(100.0))
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("Process Name");
+                });
+                if columns.pid {
                     header.col(|ui| {
                         ui.strong("PID");
                     });
+                }
+                if columns.cpu {
                     header.col(|ui| {
                         ui.strong("CPU %");
                     });
+                }
+                if columns.memory {
                     header.col(|ui| {
                         ui.strong("Memory (MB)");
                     });
+                }
+                if columns.disk {
                     header.col(|ui| {
-                        ui.strong("Action");
+                        ui.strong("Disk R / W");
                     });
-                })
-                .body(|mut body| {
-                    for process in sorted_processes.iter().take(100) {
-                        body.row(18.0, |mut row| {
-                            row.col(|ui| {
-                                ui.label(&process.name);
-                            });
-                            row.col(|ui| {
-                                ui.label(process.pid.to_string());
-                            });
-                            row.col(|ui| {
-                                ui.label(format!("{:.1}%", process.cpu_usage));
-                            });
+                }
+                if show_oom {
+                    header.col(|ui| {
+                        ui.strong("OOM Score");
+                    });
+                    header.col(|ui| {
+                        ui.strong("OOM Adj");
+                    });
+                }
+                header.col(|ui| {
+                    ui.strong("Action");
+                });
+            })
+            .body(|body| {
+                body.rows(18.0, self.visible_processes.len(), |mut row| {
+                    let process = self.visible_processes[row.index()].clone();
+                    row.set_selected(self.selected.contains(&process.pid));
+                    let depth = self.visible_depths.get(row.index()).copied().unwrap_or(0);
+                    row.col(|ui| {
+                        if depth > 0 {
+                            ui.label(format!("{}└ {}", "    ".repeat(depth - 1), process.name));
+                        } else {
+                            ui.label(&process.name);
+                        }
+                    });
+                    if columns.pid {
+                        row.col(|ui| {
+                            ui.label(process.pid.to_string());
+                        });
+                    }
+                    if columns.cpu {
+                        row.col(|ui| {
+                            ui.label(format!("{:.1}%", process.cpu_usage));
+                        });
+                    }
+                    if columns.memory {
+                        row.col(|ui| {
+                            ui.label(format!("{:.1}", process.memory_mb));
+                        });
+                    }
+                    if columns.disk {
+                        row.col(|ui| {
+                            ui.label(format!(
+                                "{} / {}",
+                                format_rate(process.disk_read_bps),
+                                format_rate(process.disk_write_bps)
+                            ));
+                        });
+                    }
+                    if show_oom {
+                        for value in [process.oom_score, process.oom_score_adj] {
                             row.col(|ui| {
-                                ui.label(format!("{:.1}", process.memory_mb));
+                                ui.label(value.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()));
                             });
-                            row.col(|ui| {
-                                if self.kill_confirmation == Some(process.pid) {
-                                    ui.horizontal(|ui| {
-                                        if ui.small_button("Confirm Kill").clicked() {
-                                            let pid = process.pid;
-                                            match force_kill_process(pid) {
-                                                KillStatus::Success => {
-                                                    self.show_notification(
-                                                        format!("SIGKILL sent to process {}", pid),
-                                                        NotificationLevel::Success,
-                                                    );
-                                                    self.kill_confirmation = None;
-                                                }
-                                                KillStatus::Failed(err) => {
-                                                    self.show_notification(
-                                                        format!("Failed to kill {}: {}", pid, err),
-                                                        NotificationLevel::Error,
-                                                    );
-                                                    self.kill_confirmation = None;
-                                                }
-                                                KillStatus::NotFound => {
-                                                    self.show_notification(
-                                                        format!("Process {} not found", pid),
-                                                        NotificationLevel::Info,
-                                                    );
-                                                    self.kill_confirmation = None;
-                                                }
-                                                _ => {}
-                                            }
-                                        }
-                                        if ui.small_button("Cancel").clicked() {
-                                            self.kill_confirmation = None;
-                                        }
-                                    });
-                                } else if ui.small_button("Force Quit").clicked() {
-                                    let pid = process.pid;
-                                    match terminate_process(pid) {
-                                        KillStatus::Success => {
-                                            self.show_notification(
-                                                format!("Process {} terminated successfully", pid),
-                                                NotificationLevel::Success,
-                                            );
-                                        }
-                                        KillStatus::RequiresConfirmation(_) => {
-                                            self.show_notification(
-                                                format!(
-                                                    "Process {} did not respond to SIGTERM. Confirm SIGKILL?",
-                                                    pid
-                                                ),
-                                                NotificationLevel::Info,
-                                            );
-                                            self.kill_confirmation = Some(pid);
-                                        }
-                                        KillStatus::Failed(err) => {
-                                            self.show_notification(
-                                                format!("Failed to terminate {}: {}", pid, err),
-                                                NotificationLevel::Error,
-                                            );
-                                        }
-                                        KillStatus::NotFound => {
-                                            self.show_notification(
-                                                format!("Process {} not found", pid),
-                                                NotificationLevel::Info,
-                                            );
-                                        }
-                                    }
+                        }
+                    }
+                    let protected = self.protection_reason(&process);
+                    row.col(|ui| {
+                        if let Some(reason) = &protected {
+                            ui.add_enabled(false, egui::Button::new("Force Quit").small())
+                                .on_disabled_hover_text(format!(
+                                    "Protected: {}. Enable \"I know what I'm doing\" in Settings to override.",
+                                    reason
+                                ));
+                        } else if self.signalling.contains(&process.pid) {
+                            ui.add_enabled(false, egui::Button::new("Signalling…").small());
+                        } else if self.kill_confirmation == Some(process.pid) {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("Confirm Kill").clicked() {
+                                    self.force_kill(process.pid, KillOrigin::Gui);
+                                }
+                                if ui.small_button("Cancel").clicked() {
+                                    self.kill_confirmation = None;
                                 }
                             });
-                        });
+                        } else if ui.small_button("Force Quit").clicked() {
+                            self.terminate(process.pid, KillOrigin::Gui);
+                        }
+                    });
+                    let response = row.response();
+                    if response.clicked() {
+                        self.handle_row_click(row.index(), modifiers);
+                    }
+                    if response.double_clicked() {
+                        self.open_details(process.pid);
                     }
+                    response.context_menu(|ui| {
+                        if ui.button("Details…").clicked() {
+                            self.open_details(process.pid);
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        self.render_priority_menu(ui, &process);
+                    });
                 });
-        });
+            });
     }
 
-    fn render_chart(&self, ui: &mut egui::Ui) {
-        ui.heading("Resource Usage");
+    fn render_priority_menu(&mut self, ui: &mut egui::Ui, process: &ProcessInfo) {
+        if self.priority_editor.as_ref().map(|e| e.pid) != Some(process.pid) {
+            #[cfg(target_os = "linux")]
+            let cpu_count = self.metrics.cpu_count();
+            #[cfg(target_os = "linux")]
+            let affinity = crate::priority::get_affinity(process.pid).unwrap_or_default();
 
-        let cpu_data = self.metrics.get_cpu_history();
-        let memory_data = self.metrics.get_memory_history();
+            self.priority_editor = Some(PriorityEditor {
+                pid: process.pid,
+                nice: get_nice(process.pid).unwrap_or(0),
+                #[cfg(target_os = "linux")]
+                cpus: (0..cpu_count).map(|cpu| affinity.contains(&cpu)).collect(),
+                #[cfg(target_os = "linux")]
+                io_class: IoPriorityClass::BestEffort,
+                #[cfg(target_os = "linux")]
+                io_level: 4,
+                #[cfg(target_os = "linux")]
+                oom_score_adj: process.oom_score_adj.unwrap_or(0),
+            });
+        }
 
-        let window_seconds = self.settings.chart_window_seconds as f64;
+        ui.label(egui::RichText::new(format!("{} ({})", process.name, process.pid)).strong());
+        ui.separator();
 
-        let current_time = if let Some(last) = cpu_data.last() {
-            last.0
-        } else {
-            0.0
+        let mut result: Option<(&str, PriorityStatus)> = None;
+        #[cfg(target_os = "linux")]
+        let mut oom_adj = None;
+        let Some(editor) = self.priority_editor.as_mut() else {
+            return;
         };
 
-        let start_time = (current_time - window_seconds).max(0.0);
+        ui.label("Nice (lower = higher priority):");
+        ui.horizontal(|ui| {
+            ui.add(egui::Slider::new(&mut editor.nice, -20..=19));
+            if ui.button("Apply").clicked() {
+                result = Some(("Nice value", set_nice(editor.pid, editor.nice)));
+            }
+        });
 
-        let cpu_line: PlotPoints = cpu_data
-            .iter()
-            .filter(|(t, _)| *t >= start_time)
-            .map(|(t, cpu)| [*t, *cpu as f64])
-            .collect();
+        #[cfg(target_os = "linux")]
+        {
+            ui.separator();
+            ui.label("CPU affinity:");
+            ui.horizontal_wrapped(|ui| {
+                for (cpu, enabled) in editor.cpus.iter_mut().enumerate() {
+                    ui.checkbox(enabled, cpu.to_string());
+                }
+            });
+            if ui.button("Apply affinity").clicked() {
+                let cpus: Vec<usize> = editor
+                    .cpus
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, enabled)| **enabled)
+                    .map(|(cpu, _)| cpu)
+                    .collect();
+                result = Some(("CPU affinity", set_affinity(editor.pid, &cpus)));
+            }
 
-        let memory_line: PlotPoints = memory_data
-            .iter()
-            .filter(|(t, _)| *t >= start_time)
-            .map(|(t, mem)| [*t, *mem])
-            .collect();
+            ui.separator();
+            ui.label("I/O priority:");
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("io_priority_class")
+                    .selected_text(editor.io_class.label())
+                    .show_ui(ui, |ui| {
+                        for class in [
+                            IoPriorityClass::RealTime,
+                            IoPriorityClass::BestEffort,
+                            IoPriorityClass::Idle,
+                        ] {
+                            ui.selectable_value(&mut editor.io_class, class, class.label());
+                        }
+                    });
+                if editor.io_class != IoPriorityClass::Idle {
+                    ui.add(egui::Slider::new(&mut editor.io_level, 0..=7));
+                }
+                if ui.button("Apply").clicked() {
+                    result = Some((
+                        "I/O priority",
+                        set_io_priority(editor.pid, editor.io_class, editor.io_level),
+                    ));
+                }
+            });
 
-        Plot::new("resource_chart")
-            .view_aspect(2.5)
-            .legend(egui_plot::Legend::default())
-            .show(ui, |plot_ui| {
-                plot_ui.line(
-                    Line::new(cpu_line)
-                        .name("CPU %")
-                        .color(egui::Color32::from_rgb(75, 150, 220)),
-                );
-                plot_ui.line(
-                    Line::new(memory_line)
-                        .name(format!("Memory (GB) / {:.1} GB total", self.metrics.get_total_memory_gb()))
-                        .color(egui::Color32::from_rgb(255, 140, 0)),
-                );
+            ui.separator();
+            ui.label(format!(
+                "OOM score adjustment (current score {}):",
+                process.oom_score.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+            ));
+            ui.horizontal(|ui| {
+                ui.add(egui::Slider::new(&mut editor.oom_score_adj, -1000..=1000));
+                if ui.button("Apply").clicked() {
+                    oom_adj = Some(editor.oom_score_adj);
+                }
             });
-    }
+            ui.horizontal(|ui| {
+                if ui.small_button("Kill this first (+500)").clicked() {
+                    oom_adj = Some((editor.oom_score_adj + 500).min(1000));
+                }
+                if ui.small_button("Spare this (-500)").clicked() {
+                    oom_adj = Some((editor.oom_score_adj - 500).max(-1000));
+                }
+            });
+            if let Some(adj) = oom_adj {
+                result = Some(("OOM score adjustment", set_oom_score_adj(editor.pid, adj)));
+            }
+        }
 
-    fn render_settings(&mut self, ctx: &egui::Context) {
-        if !self.show_settings {
-            return;
+        if let Some((what, status)) = result {
+            let pid = process.pid;
+            match status {
+                PriorityStatus::Success => {
+                    // Keep the +/-500 buttons relative to what was just
+                    // written, and don't show the cached score until the
+                    // next OOM refresh tick.
+                    #[cfg(target_os = "linux")]
+                    if let Some(adj) = oom_adj {
+                        if let Some(editor) = self.priority_editor.as_mut() {
+                            editor.oom_score_adj = adj;
+                        }
+                        self.metrics.invalidate_oom_scores(pid);
+                    }
+                    self.show_notification(
+                        format!("{} updated for process {}", what, pid),
+                        NotificationLevel::Success,
+                    );
+                }
+                PriorityStatus::Failed(err) => {
+                    self.show_notification(
+                        format!("Failed to update {} for process {}: {}", what, pid, err),
+                        NotificationLevel::Error,
+                    );
+                }
+            }
+            ui.close_menu();
         }
+    }
 
-        let mut should_close = false;
-        egui::Window::new("Settings")
-            .open(&mut self.show_settings)
-            .resizable(false)
-            .show(ctx, |ui| {
-                ui.heading("Chart Settings");
+    /// Runs `work` with a copy of the kill context on a worker thread, so
+    /// escalation waits never block the UI, and wakes the UI with the result.
+    fn spawn_kill(&self, origin: KillOrigin, work: impl FnOnce(&KillContext) -> KillOutcome + Send + 'static) {
+        let guard = self.kill_guard().cloned();
+        let escalation = self.settings.escalation.clone();
+        let sender = self.kill_sender.clone();
+        let egui_ctx = self.egui_ctx.clone();
+        thread::spawn(move || {
+            let ctx = KillContext {
+                guard: guard.as_ref(),
+                escalation: &escalation,
+                origin,
+            };
+            let _ = sender.send(work(&ctx));
+            egui_ctx.request_repaint();
+        });
+    }
 
-                ui.horizontal(|ui| {
-                    ui.label("Chart window (seconds):");
-                    let mut window = self.settings.chart_window_seconds as i32;
+    fn terminate(&mut self, pid: u32, origin: KillOrigin) {
+        if self.signalling.insert(pid) {
+            self.spawn_kill(origin, move |ctx| KillOutcome::Terminate(pid, terminate_process(pid, ctx)));
+        }
+    }
+
+    fn force_kill(&mut self, pid: u32, origin: KillOrigin) {
+        if self.signalling.insert(pid) {
+            self.spawn_kill(origin, move |ctx| KillOutcome::ForceKill(pid, force_kill_process(pid, ctx)));
+        }
+    }
+
+    fn handle_kill_outcome(&mut self, outcome: KillOutcome) {
+        match outcome {
+            KillOutcome::Terminate(pid, status) => {
+                self.signalling.remove(&pid);
+                self.report_terminate(pid, status);
+            }
+            KillOutcome::ForceKill(pid, status) => {
+                self.signalling.remove(&pid);
+                self.report_force_kill(pid, status);
+            }
+            KillOutcome::Batch(action, results) => self.report_batch(action, results),
+        }
+    }
+
+    fn report_terminate(&mut self, pid: u32, status: KillStatus) {
+        match status {
+            KillStatus::Success => {
+                self.show_notification(
+                    format!("Process {} terminated successfully", pid),
+                    NotificationLevel::Success,
+                );
+            }
+            KillStatus::RequiresConfirmation(_) => {
+                self.show_notification(
+                    format!("Process {} is still running after its escalation policy. Confirm SIGKILL?", pid),
+                    NotificationLevel::Info,
+                );
+                self.kill_confirmation = Some(pid);
+            }
+            KillStatus::Failed(err) => {
+                self.show_notification(
+                    format!("Failed to terminate {}: {}", pid, err),
+                    NotificationLevel::Error,
+                );
+            }
+            KillStatus::NotFound => {
+                self.show_notification(format!("Process {} not found", pid), NotificationLevel::Info);
+            }
+            KillStatus::Refused(reason) => {
+                self.show_notification(
+                    format!("Refused to terminate {}: {}", pid, reason),
+                    NotificationLevel::Error,
+                );
+            }
+        }
+    }
+
+    fn report_force_kill(&mut self, pid: u32, status: KillStatus) {
+        match status {
+            KillStatus::Success => {
+                self.show_notification(
+                    format!("SIGKILL sent to process {}", pid),
+                    NotificationLevel::Success,
+                );
+            }
+            KillStatus::Failed(err) => {
+                self.show_notification(
+                    format!("Failed to kill {}: {}", pid, err),
+                    NotificationLevel::Error,
+                );
+            }
+            KillStatus::NotFound => {
+                self.show_notification(format!("Process {} not found", pid), NotificationLevel::Info);
+            }
+            KillStatus::Refused(reason) => {
+                self.show_notification(
+                    format!("Refused to kill {}: {}", pid, reason),
+                    NotificationLevel::Error,
+                );
+            }
+            KillStatus::RequiresConfirmation(_) => {}
+        }
+        if self.kill_confirmation == Some(pid) {
+            self.kill_confirmation = None;
+        }
+    }
+
+    fn render_sensors(&self, ui: &mut egui::Ui) {
+        let temperature = self.metrics.cpu_temperature();
+        let battery = self.metrics.battery();
+        if temperature.is_none() && battery.is_none() {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            if let Some(temperature) = temperature {
+                ui.label(format!("🌡 CPU {:.0}°C", temperature));
+            }
+            if let Some(battery) = battery {
+                if temperature.is_some() {
+                    ui.separator();
+                }
+                let mut text = format!("🔋 {:.0}% {}", battery.charge_percent, battery.state);
+                if let Some(watts) = battery.power_watts {
+                    text.push_str(&format!(", {:.1} W", watts));
+                }
+                if let Some(online) = battery.ac_online {
+                    text.push_str(if online { ", AC connected" } else { ", on battery" });
+                }
+                ui.label(text);
+            }
+        });
+
+        let components = self.metrics.components().list();
+        if !components.is_empty() {
+            egui::CollapsingHeader::new("All sensors").show(ui, |ui| {
+                egui::Grid::new("sensors_grid").striped(true).num_columns(3).show(ui, |ui| {
+                    for component in components {
+                        ui.label(component.label());
+                        ui.label(format!("{:.0}°C", component.temperature()));
+                        ui.label(match component.critical() {
+                            Some(critical) => format!("critical {:.0}°C", critical),
+                            None => String::new(),
+                        });
+                        ui.end_row();
+                    }
+                });
+            });
+        }
+    }
+
+    fn render_filesystems(&self, ui: &mut egui::Ui) {
+        let filesystems = self.metrics.filesystems();
+        if filesystems.is_empty() {
+            return;
+        }
+
+        let threshold = self.settings.disk_alert_percent;
+        let flagged = filesystems.iter().filter(|fs| fs.used_percent() >= threshold).count();
+        let title = if flagged > 0 {
+            format!("Filesystems ({} above {:.0}%)", flagged, threshold)
+        } else {
+            format!("Filesystems ({})", filesystems.len())
+        };
+
+        egui::CollapsingHeader::new(title).id_salt("filesystems").show(ui, |ui| {
+            egui::Grid::new("filesystems_grid").striped(true).num_columns(6).show(ui, |ui| {
+                for header in ["Mount", "Type", "Used", "Free", "Use %", "Inodes %"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+
+                for filesystem in filesystems {
+                    let used_percent = filesystem.used_percent();
+                    let over = used_percent >= threshold;
+                    let used_gb = (filesystem.total_bytes - filesystem.available_bytes.min(filesystem.total_bytes)) as f64
+                        / 1_073_741_824.0;
+
+                    ui.label(&filesystem.mount_point);
+                    ui.label(&filesystem.file_system);
+                    ui.label(format!("{:.1} / {:.1} GB", used_gb, filesystem.total_bytes as f64 / 1_073_741_824.0));
+                    ui.label(format!("{:.1} GB", filesystem.available_bytes as f64 / 1_073_741_824.0));
+                    let percent = egui::RichText::new(format!("{}{:.0}%", if over { "⚠ " } else { "" }, used_percent));
+                    ui.label(if over {
+                        percent.color(egui::Color32::from_rgb(220, 53, 69))
+                    } else {
+                        percent
+                    });
+                    ui.label(
+                        filesystem
+                            .inode_used_percent()
+                            .map(|p| format!("{:.0}%", p))
+                            .unwrap_or_else(|| "-".to_string()),
+                    );
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn render_chart(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Resource Usage");
+            ui.menu_button("Series", |ui| {
+                let mut changed = false;
+                for line in ChartLine::ALL {
+                    changed |= ui.checkbox(self.settings.chart_series.get_mut(line), line.label()).changed();
+                }
+                if changed {
+                    let _ = self.settings.save();
+                }
+            });
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let selected = self.settings.chart_network_interface.clone();
+                egui::ComboBox::from_id_salt("network_interface")
+                    .selected_text(selected.as_deref().unwrap_or("None"))
+                    .show_ui(ui, |ui| {
+                        let mut choice = selected.clone();
+                        ui.selectable_value(&mut choice, None, "None");
+                        for interface in self.metrics.network_interfaces() {
+                            ui.selectable_value(&mut choice, Some(interface.to_string()), interface);
+                        }
+                        if choice != selected {
+                            self.settings.chart_network_interface = choice;
+                            let _ = self.settings.save();
+                        }
+                    });
+                ui.label("Network:");
+
+                let selected = self.settings.chart_mount_point.clone();
+                egui::ComboBox::from_id_salt("chart_mount_point")
+                    .selected_text(selected.as_deref().unwrap_or("None"))
+                    .show_ui(ui, |ui| {
+                        let mut choice = selected.clone();
+                        ui.selectable_value(&mut choice, None, "None");
+                        for filesystem in self.metrics.filesystems() {
+                            let mount_point = filesystem.mount_point.clone();
+                            ui.selectable_value(&mut choice, Some(mount_point.clone()), mount_point);
+                        }
+                        if choice != selected {
+                            self.settings.chart_mount_point = choice;
+                            let _ = self.settings.save();
+                        }
+                    });
+                ui.label("Filesystem:");
+            });
+        });
+
+        let cpu_data = self.metrics.get_cpu_history();
+        let memory_data = self.metrics.get_memory_history();
+        let disk_data = self.metrics.get_disk_history();
+
+        let window_seconds = self.settings.chart_window_seconds as f64;
+
+        let current_time = if let Some(last) = cpu_data.last() {
+            last.0
+        } else {
+            0.0
+        };
+
+        let start_time = (current_time - window_seconds).max(0.0);
+
+        let cpu_line: PlotPoints = cpu_data
+            .iter()
+            .filter(|(t, _)| *t >= start_time)
+            .map(|(t, cpu)| [*t, *cpu as f64])
+            .collect();
+
+        let memory_line: PlotPoints = memory_data
+            .iter()
+            .filter(|(t, _)| *t >= start_time)
+            .map(|(t, mem)| [*t, *mem])
+            .collect();
+
+        let disk_line: PlotPoints = disk_data
+            .iter()
+            .filter(|(t, _)| *t >= start_time)
+            .map(|(t, rate)| [*t, *rate])
+            .collect();
+
+        let temperature_line: PlotPoints = self
+            .metrics
+            .get_temperature_history()
+            .iter()
+            .filter(|(t, _)| *t >= start_time)
+            .map(|(t, celsius)| [*t, *celsius as f64])
+            .collect();
+
+        let battery_line: PlotPoints = self
+            .metrics
+            .get_battery_history()
+            .iter()
+            .filter(|(t, _)| *t >= start_time)
+            .map(|(t, percent)| [*t, *percent as f64])
+            .collect();
+
+        let churn_line: PlotPoints = self
+            .metrics
+            .process_tracker()
+            .get_churn_history()
+            .iter()
+            .filter(|(t, _)| *t >= start_time)
+            .map(|(t, rate)| [*t, *rate])
+            .collect();
+
+        let filesystem_data = self
+            .settings
+            .chart_mount_point
+            .as_deref()
+            .map(|mount_point| (mount_point, self.metrics.get_filesystem_history(mount_point)));
+
+        let network_data = self
+            .settings
+            .chart_network_interface
+            .as_deref()
+            .map(|interface| (interface, self.metrics.get_network_history(interface)));
+
+        let series = self.settings.chart_series;
+        Plot::new("resource_chart")
+            .view_aspect(2.5)
+            .legend(egui_plot::Legend::default())
+            .show(ui, |plot_ui| {
+                if series.cpu {
+                    plot_ui.line(
+                        Line::new(cpu_line)
+                            .name("CPU %")
+                            .color(egui::Color32::from_rgb(75, 150, 220)),
+                    );
+                }
+                if series.memory {
+                    plot_ui.line(
+                        Line::new(memory_line)
+                            .name(format!("Memory (GB) / {:.1} GB total", self.metrics.get_total_memory_gb()))
+                            .color(egui::Color32::from_rgb(255, 140, 0)),
+                    );
+                }
+                if series.disk {
+                    plot_ui.line(
+                        Line::new(disk_line)
+                            .name("Disk I/O (MB/s)")
+                            .color(egui::Color32::from_rgb(150, 90, 200)),
+                    );
+                }
+                if series.churn {
+                    plot_ui.line(
+                        Line::new(churn_line)
+                            .name("Process churn (events/s)")
+                            .color(egui::Color32::from_rgb(90, 200, 200)),
+                    );
+                }
+                if series.temperature && !self.metrics.get_temperature_history().is_empty() {
+                    plot_ui.line(
+                        Line::new(temperature_line)
+                            .name("CPU temperature (°C)")
+                            .color(egui::Color32::from_rgb(230, 80, 40)),
+                    );
+                }
+                if series.battery && !self.metrics.get_battery_history().is_empty() {
+                    plot_ui.line(
+                        Line::new(battery_line)
+                            .name("Battery (%)")
+                            .color(egui::Color32::from_rgb(200, 200, 60)),
+                    );
+                }
+                if let Some((mount_point, samples)) = filesystem_data {
+                    let line: PlotPoints = samples
+                        .iter()
+                        .filter(|(t, _)| *t >= start_time)
+                        .map(|(t, percent)| [*t, *percent as f64])
+                        .collect();
+                    plot_ui.line(
+                        Line::new(line)
+                            .name(format!("{} used (%)", mount_point))
+                            .color(egui::Color32::from_rgb(120, 120, 120)),
+                    );
+                }
+                if let Some((interface, samples)) = network_data {
+                    let visible = samples.iter().filter(|(t, _, _)| *t >= start_time);
+                    let rx_line: PlotPoints = visible
+                        .clone()
+                        .map(|(t, rx, _)| [*t, rx / 1_048_576.0])
+                        .collect();
+                    let tx_line: PlotPoints = visible.map(|(t, _, tx)| [*t, tx / 1_048_576.0]).collect();
+                    plot_ui.line(
+                        Line::new(rx_line)
+                            .name(format!("{} rx (MB/s)", interface))
+                            .color(egui::Color32::from_rgb(60, 179, 113)),
+                    );
+                    plot_ui.line(
+                        Line::new(tx_line)
+                            .name(format!("{} tx (MB/s)", interface))
+                            .color(egui::Color32::from_rgb(220, 53, 69)),
+                    );
+                }
+            });
+    }
+
+    fn render_settings(&mut self, ctx: &egui::Context) {
+        if !self.show_settings {
+            return;
+        }
+
+        let mut should_close = false;
+        let mut switch_to = None;
+        let mut hotkey_changed = false;
+        egui::Window::new("Settings")
+            .open(&mut self.show_settings)
+            .resizable(false)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                ui.heading("Profiles");
+                ui.label("A profile keeps its own refresh rate, sort, filter, columns and chart settings; edits go to the active profile.");
+                let names: Vec<String> = self.settings.profiles.keys().cloned().collect();
+                ui.horizontal(|ui| {
+                    ui.label("Active:");
+                    egui::ComboBox::from_id_salt("active_profile")
+                        .selected_text(self.settings.active_profile.as_deref().unwrap_or("(none)"))
+                        .show_ui(ui, |ui| {
+                            for name in &names {
+                                let active = self.settings.active_profile.as_ref() == Some(name);
+                                if ui.selectable_label(active, name).clicked() && !active {
+                                    switch_to = Some(name.clone());
+                                }
+                            }
+                        });
+                    if let Some(active) = self.settings.active_profile.clone() {
+                        if ui.button("Delete").clicked() {
+                            self.settings.delete_profile(&active);
+                            let _ = self.settings.save();
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.new_profile_name);
+                    let name = self.new_profile_name.trim().to_string();
+                    if ui.add_enabled(!name.is_empty(), egui::Button::new("Save current as profile")).clicked() {
+                        self.settings.save_profile_as(&name);
+                        let _ = self.settings.save();
+                        self.new_profile_name.clear();
+                    }
+                });
+                ui.separator();
+
+                ui.heading("Chart Settings");
+
+                ui.horizontal(|ui| {
+                    ui.label("Chart window (seconds):");
+                    let mut window = self.settings.chart_window_seconds as i32;
                     if ui.add(egui::Slider::new(&mut window, 60..=300)).changed() {
                         self.settings.chart_window_seconds = window as u32;
                         let _ = self.settings.save();
@@ -320,37 +1355,852 @@ impl MemwatchApp {
                 });
 
                 ui.horizontal(|ui| {
-                    ui.label("Refresh interval (ms):");
-                    let mut interval = self.settings.refresh_interval_ms as i32;
-                    if ui.add(egui::Slider::new(&mut interval, 500..=2000)).changed() {
-                        self.settings.refresh_interval_ms = interval as u64;
-                        let _ = self.settings.save();
+                    ui.label("Refresh interval (ms):");
+                    let mut interval = self.settings.refresh_interval_ms as i32;
+                    if ui.add(egui::Slider::new(&mut interval, 250..=5000)).changed() {
+                        self.settings.refresh_interval_ms = interval as u64;
+                        let _ = self.settings.save();
+                    }
+                });
+
+                if cfg!(target_os = "linux") {
+                    ui.horizontal(|ui| {
+                        ui.label("Process data source:");
+                        let before = self.settings.process_source;
+                        egui::ComboBox::from_id_salt("process_source")
+                            .selected_text(before.label())
+                            .show_ui(ui, |ui| {
+                                for kind in [ProcessSourceKind::Sysinfo, ProcessSourceKind::Procfs] {
+                                    ui.selectable_value(&mut self.settings.process_source, kind, kind.label());
+                                }
+                            });
+                        if self.settings.process_source != before {
+                            self.metrics.set_source(self.settings.process_source);
+                            let _ = self.settings.save();
+                        }
+                    });
+                    let cost = self.metrics.sampling_cost();
+                    ui.label(format!(
+                        "Last sample: {:.1} ms, {} processes{}",
+                        cost.duration.as_secs_f64() * 1000.0,
+                        cost.processes_scanned,
+                        cost.files_read
+                            .map(|files| format!(", {} files read", files))
+                            .unwrap_or_default()
+                    ));
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Flag filesystems above (%):");
+                    if ui
+                        .add(egui::Slider::new(&mut self.settings.disk_alert_percent, 50.0..=99.0))
+                        .changed()
+                    {
+                        let _ = self.settings.save();
+                    }
+                });
+
+                ui.separator();
+
+                ui.heading("Adaptive Refresh");
+                let policy = &mut self.settings.refresh_policy;
+                let mut policy_changed = ui
+                    .checkbox(&mut policy.adaptive, "Adjust the refresh interval automatically")
+                    .changed();
+                ui.add_enabled_ui(policy.adaptive, |ui| {
+                    egui::Grid::new("refresh_policy_grid").num_columns(2).show(ui, |ui| {
+                        ui.label("While hidden:");
+                        policy_changed |= ui
+                            .add(egui::DragValue::new(&mut policy.hidden_interval_ms).range(500..=60_000).suffix(" ms"))
+                            .changed();
+                        ui.end_row();
+
+                        ui.label("When idle:");
+                        policy_changed |= ui
+                            .add(egui::DragValue::new(&mut policy.idle_interval_ms).range(500..=30_000).suffix(" ms"))
+                            .changed();
+                        ui.end_row();
+
+                        ui.label("Idle means CPU below:");
+                        ui.horizontal(|ui| {
+                            policy_changed |= ui
+                                .add(egui::DragValue::new(&mut policy.idle_cpu_percent).range(0.0..=100.0).suffix(" %"))
+                                .changed();
+                            ui.label("for");
+                            policy_changed |= ui
+                                .add(egui::DragValue::new(&mut policy.idle_after_seconds).range(1..=600).suffix(" s"))
+                                .changed();
+                        });
+                        ui.end_row();
+
+                        ui.label("After a spike:");
+                        ui.horizontal(|ui| {
+                            policy_changed |= ui
+                                .add(egui::DragValue::new(&mut policy.burst_interval_ms).range(100..=2_000).suffix(" ms"))
+                                .changed();
+                            ui.label("for");
+                            policy_changed |= ui
+                                .add(egui::DragValue::new(&mut policy.burst_seconds).range(1..=600).suffix(" s"))
+                                .changed();
+                        });
+                        ui.end_row();
+
+                        ui.label("Spike means CPU change of:");
+                        policy_changed |= ui
+                            .add(egui::DragValue::new(&mut policy.burst_cpu_delta).range(1.0..=100.0).suffix(" pts"))
+                            .changed();
+                        ui.end_row();
+
+                        ui.label("or memory change of:");
+                        policy_changed |= ui
+                            .add(egui::DragValue::new(&mut policy.burst_memory_delta_mb).range(10.0..=65_536.0).suffix(" MB"))
+                            .changed();
+                        ui.end_row();
+                    });
+                });
+                ui.label(format!(
+                    "Currently every {} ms ({})",
+                    self.refresh_interval.0.as_millis(),
+                    self.refresh_interval.1.label()
+                ));
+                if policy_changed {
+                    let _ = self.settings.save();
+                }
+
+                ui.separator();
+
+                ui.heading("Status footer");
+                if ui
+                    .checkbox(&mut self.settings.show_status_footer, "Show memwatch's own resource usage")
+                    .changed()
+                {
+                    let _ = self.settings.save();
+                }
+
+                ui.separator();
+
+                ui.heading("Hotkey");
+                let toggle_key = self.settings.keybindings.toggle_window.clone();
+                if ui
+                    .checkbox(&mut self.settings.hotkey_enabled, format!("Enable global hotkey ({})", toggle_key))
+                    .changed()
+                {
+                    let _ = self.settings.save();
+                    hotkey_changed = true;
+                }
+
+                if let Some(hotkey) = &self.hotkey_manager {
+                    if self.settings.hotkey_enabled {
+                        ui.label(hotkey.describe());
+                    }
+                } else {
+                    ui.label("⚠ Global hotkey not available on this platform");
+                }
+
+                ui.label("You can also use 'memwatch toggle' from CLI");
+
+                ui.separator();
+
+                ui.heading("Keyboard Shortcuts");
+                ui.label("e.g. \"K\", \"Shift+K\", \"Ctrl+Alt+Delete\". Cmd is ⌘ on macOS and Ctrl elsewhere; leave empty to unbind.");
+                let mut bindings_changed = false;
+                egui::Grid::new("keybindings_grid").num_columns(3).show(ui, |ui| {
+                    for (action, text) in KeyAction::ALL.iter().zip(self.keybinding_text.iter_mut()) {
+                        ui.label(action.label());
+                        let edited = ui.add(egui::TextEdit::singleline(text).desired_width(120.0)).changed();
+                        let parsed = if text.trim().is_empty() {
+                            Ok(())
+                        } else {
+                            KeyBinding::parse(text).map(|_| ())
+                        };
+                        match parsed {
+                            Ok(()) => {
+                                if edited {
+                                    *self.settings.keybindings.get_mut(*action) = text.trim().to_string();
+                                    bindings_changed = true;
+                                }
+                                ui.label("");
+                            }
+                            Err(e) => {
+                                ui.colored_label(egui::Color32::from_rgb(220, 53, 69), e);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                for conflict in self.settings.keybindings.conflicts() {
+                    ui.colored_label(egui::Color32::from_rgb(220, 53, 69), format!("⚠ {}", conflict));
+                }
+                if ui.button("Reset shortcuts").clicked() {
+                    self.settings.keybindings = KeyBindings::default();
+                    self.keybinding_text = keybindings_to_text(&self.settings.keybindings);
+                    bindings_changed = true;
+                }
+                if bindings_changed {
+                    self.keymap = self.settings.keybindings.keymap().0;
+                    let _ = self.settings.save();
+                    hotkey_changed = true;
+                }
+
+                ui.separator();
+
+                ui.heading("Process Protection");
+                ui.label("Kill actions refuse these processes (one entry per line).");
+                let mut protection_changed = false;
+                egui::Grid::new("protection_lists").num_columns(2).show(ui, |ui| {
+                    for (label, text) in ["Names", "Path prefixes", "PIDs", "UIDs"]
+                        .iter()
+                        .zip(self.protection_text.iter_mut())
+                    {
+                        ui.label(*label);
+                        protection_changed |= ui
+                            .add(egui::TextEdit::multiline(text).desired_rows(2))
+                            .changed();
+                        ui.end_row();
+                    }
+                });
+                if protection_changed {
+                    self.settings.protection = protection_lists_from_text(&self.protection_text);
+                    let _ = self.settings.save();
+                }
+                if ui.button("Restore defaults").clicked() {
+                    self.settings.protection = ProtectionRules::default();
+                    self.protection_text = protection_lists_to_text(&self.settings.protection);
+                    let _ = self.settings.save();
+                }
+                ui.checkbox(
+                    &mut self.allow_protected_kills,
+                    "I know what I'm doing: allow killing protected processes",
+                )
+                .on_hover_text("Applies to this session only and is never saved");
+
+                ui.separator();
+
+                ui.heading("Termination Policies");
+                ui.label("Force Quit sends each signal in turn and waits for the process to exit.");
+                let mut escalation_changed = false;
+                egui::CollapsingHeader::new("Default policy").show(ui, |ui| {
+                    escalation_changed |= escalation_policy_editor(ui, &mut self.settings.escalation.default, false);
+                });
+                let mut remove: Option<usize> = None;
+                for (index, policy) in self.settings.escalation.overrides.iter_mut().enumerate() {
+                    let title = if policy.process_name.is_empty() {
+                        "(unnamed)".to_string()
+                    } else {
+                        policy.process_name.clone()
+                    };
+                    egui::CollapsingHeader::new(title)
+                        .id_salt(("escalation_policy", index))
+                        .show(ui, |ui| {
+                            escalation_changed |= escalation_policy_editor(ui, policy, true);
+                            if ui.small_button("Remove policy").clicked() {
+                                remove = Some(index);
+                            }
+                        });
+                }
+                if let Some(index) = remove {
+                    self.settings.escalation.overrides.remove(index);
+                    escalation_changed = true;
+                }
+                if ui.button("Add policy").clicked() {
+                    self.settings.escalation.overrides.push(EscalationPolicy::default());
+                    escalation_changed = true;
+                }
+                if escalation_changed {
+                    let _ = self.settings.save();
+                }
+
+                ui.separator();
+
+                ui.heading("OOM Guard");
+                let guard = &mut self.settings.oom_guard;
+                let mut guard_changed = false;
+                guard_changed |= ui
+                    .checkbox(&mut guard.enabled, "Act on the top offender under memory pressure")
+                    .changed();
+                guard_changed |= ui
+                    .checkbox(&mut guard.dry_run, "Dry run (only log what would be signalled)")
+                    .changed();
+                egui::Grid::new("oom_guard_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Available memory below:");
+                    guard_changed |= ui
+                        .add(egui::DragValue::new(&mut guard.min_available_mb).range(0..=65_536).suffix(" MB"))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("PSI full avg10 above:");
+                    guard_changed |= ui
+                        .add(egui::DragValue::new(&mut guard.psi_full_limit).range(0.0..=100.0).suffix(" %"))
+                        .on_hover_text("Linux only")
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Sustained for:");
+                    guard_changed |= ui
+                        .add(egui::DragValue::new(&mut guard.sustain_seconds).range(1..=600).suffix(" s"))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Cooldown between actions:");
+                    guard_changed |= ui
+                        .add(egui::DragValue::new(&mut guard.cooldown_seconds).range(0..=3_600).suffix(" s"))
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Victim:");
+                    egui::ComboBox::from_id_salt("oom_victim_policy")
+                        .selected_text(guard.victim_policy.label())
+                        .show_ui(ui, |ui| {
+                            for policy in [
+                                VictimPolicy::LargestRss,
+                                VictimPolicy::FastestGrowth,
+                                VictimPolicy::Allowlist,
+                            ] {
+                                guard_changed |= ui
+                                    .selectable_value(&mut guard.victim_policy, policy, policy.label())
+                                    .changed();
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Action:");
+                    ui.horizontal(|ui| {
+                        guard_changed |= ui
+                            .radio_value(&mut guard.action, GuardAction::Terminate, "SIGTERM")
+                            .changed();
+                        guard_changed |= ui
+                            .radio_value(&mut guard.action, GuardAction::Stop, "SIGSTOP")
+                            .changed();
+                    });
+                    ui.end_row();
+                });
+                if guard.victim_policy == VictimPolicy::Allowlist {
+                    ui.label("Allowlist (comma-separated process names):");
+                    if ui.text_edit_singleline(&mut self.oom_allowlist_text).changed() {
+                        guard.allowlist = self
+                            .oom_allowlist_text
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|name| !name.is_empty())
+                            .map(str::to_string)
+                            .collect();
+                        guard_changed = true;
+                    }
+                }
+                if guard_changed {
+                    let _ = self.settings.save();
+                }
+
+                if ui.button("Close").clicked() {
+                    should_close = true;
+                }
+            });
+
+        if should_close {
+            self.show_settings = false;
+        }
+        if let Some(name) = switch_to {
+            self.switch_profile(&name);
+        }
+        if hotkey_changed {
+            self.sync_global_hotkey();
+        }
+    }
+
+    fn open_details(&mut self, pid: u32) {
+        self.details = Some((pid, sockets_for_pid(pid)));
+    }
+
+    fn render_details(&mut self, ctx: &egui::Context) {
+        let Some((pid, sockets)) = &self.details else {
+            return;
+        };
+        let pid = *pid;
+        let process = self.processes.iter().find(|p| p.pid == pid);
+
+        let mut open = true;
+        let mut refresh = false;
+        egui::Window::new(format!("Process {}", pid))
+            .open(&mut open)
+            .default_size([520.0, 360.0])
+            .show(ctx, |ui| {
+                let Some(process) = process else {
+                    ui.label("Process has exited.");
+                    return;
+                };
+
+                egui::Grid::new("details_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Name:");
+                    ui.label(&process.name);
+                    ui.end_row();
+                    ui.label("Executable:");
+                    ui.label(process.exe.as_deref().unwrap_or("-"));
+                    ui.end_row();
+                    ui.label("User:");
+                    ui.label(match (&process.user, process.uid) {
+                        (Some(user), Some(uid)) => format!("{} ({})", user, uid),
+                        (None, Some(uid)) => uid.to_string(),
+                        _ => "-".to_string(),
+                    });
+                    ui.end_row();
+                    ui.label("Session:");
+                    ui.label(process.session_id.map(|sid| sid.to_string()).unwrap_or_else(|| "-".to_string()));
+                    ui.end_row();
+                    ui.label("CPU:");
+                    ui.label(format!("{:.1}%", process.cpu_usage));
+                    ui.end_row();
+                    ui.label("Memory:");
+                    ui.label(format!("{:.1} MB", process.memory_mb));
+                    ui.end_row();
+                    ui.label("Disk read / write:");
+                    ui.label(format!(
+                        "{} / {}",
+                        format_rate(process.disk_read_bps),
+                        format_rate(process.disk_write_bps)
+                    ));
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.heading("Network");
+                    if ui.small_button("Refresh").clicked() {
+                        refresh = true;
+                    }
+                });
+
+                if !cfg!(target_os = "linux") {
+                    ui.label("Socket attribution is only available on Linux.");
+                    return;
+                }
+
+                let listening: Vec<&SocketInfo> = sockets.iter().filter(|s| s.is_listening()).collect();
+                let connections: Vec<&SocketInfo> = sockets.iter().filter(|s| !s.is_listening()).collect();
+
+                ui.strong(format!("Listening ({})", listening.len()));
+                for socket in &listening {
+                    ui.label(format!("{}  {}", socket.protocol, socket.local));
+                }
+
+                ui.strong(format!("Connections ({})", connections.len()));
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    egui::Grid::new("connections_grid").striped(true).num_columns(4).show(ui, |ui| {
+                        for socket in &connections {
+                            ui.label(socket.protocol);
+                            ui.label(socket.local.to_string());
+                            ui.label(socket.remote.to_string());
+                            ui.label(socket.state);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        if !open {
+            self.details = None;
+        } else if refresh {
+            self.open_details(pid);
+        }
+    }
+
+    fn render_changes(&mut self, ui: &mut egui::Ui) {
+        let ctx = ui.ctx().clone();
+        ui.horizontal(|ui| {
+            ui.heading("Snapshot");
+            if ui.button("Pin snapshot").clicked() {
+                self.run_action(&ctx, Action::PinSnapshot);
+            }
+            if ui
+                .button("Export")
+                .on_hover_text("Save the pinned snapshot, or the live process list, as JSON")
+                .clicked()
+            {
+                self.run_action(&ctx, Action::ExportSnapshot);
+            }
+            if self.pinned_snapshot.is_some() && ui.button("Clear").clicked() {
+                self.pinned_snapshot = None;
+                self.snapshot_diff = None;
+            }
+            if let Some(snapshot) = &self.pinned_snapshot {
+                ui.label(format!(
+                    "Comparing live state to snapshot from {} ago",
+                    format_duration(snapshot.taken_at.elapsed())
+                ));
+            }
+        });
+
+        ui.columns(2, |columns| {
+            columns[0].strong("Start/exit log");
+            egui::ScrollArea::vertical()
+                .id_salt("event_log")
+                .show(&mut columns[0], |ui| {
+                    egui::Grid::new("event_log_grid").striped(true).num_columns(6).show(ui, |ui| {
+                        for header in ["Time", "Event", "PID", "Name", "Parent", "Lifetime / Memory"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+
+                        for event in self.metrics.process_tracker().events() {
+                            ui.label(audit::format_timestamp(event.timestamp));
+                            ui.label(match event.kind {
+                                ProcessEventKind::Started => "▶ start",
+                                ProcessEventKind::Exited => "■ exit",
+                            });
+                            ui.label(event.pid.to_string());
+                            ui.label(&event.name);
+                            ui.label(event.parent.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()));
+                            ui.label(match event.lifetime {
+                                Some(lifetime) => {
+                                    format!("{}, {:.1} MB", format_duration(lifetime), event.memory_mb)
+                                }
+                                None => format!("{:.1} MB", event.memory_mb),
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+
+            let Some(diff) = &self.snapshot_diff else {
+                columns[1].label("Pin a snapshot to compare the live state against it.");
+                return;
+            };
+            egui::ScrollArea::vertical()
+                .id_salt("snapshot_diff")
+                .show(&mut columns[1], |ui| {
+                    ui.strong(format!("New processes ({})", diff.new.len()));
+                    for process in &diff.new {
+                        let parent = process.parent.map(|p| format!(", parent {}", p)).unwrap_or_default();
+                        ui.label(format!(
+                            "+ {} ({}{}), {:.1} MB",
+                            process.name, process.pid, parent, process.memory_mb
+                        ));
+                    }
+                    ui.strong(format!("Gone processes ({})", diff.gone.len()));
+                    for process in &diff.gone {
+                        ui.label(format!("- {} ({}), {:.1} MB", process.name, process.pid, process.memory_mb));
+                    }
+                    ui.strong("Changes");
+                    egui::Grid::new("snapshot_delta_grid").striped(true).num_columns(4).show(ui, |ui| {
+                        for header in ["Process", "PID", "Memory Δ", "CPU Δ"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for delta in &diff.changed {
+                            ui.label(&delta.name);
+                            ui.label(delta.pid.to_string());
+                            ui.label(format!(
+                                "{:+.1} MB ({:.1} → {:.1})",
+                                delta.memory_delta_mb(),
+                                delta.memory_before_mb,
+                                delta.memory_after_mb
+                            ));
+                            ui.label(format!("{:+.1}%", delta.cpu_delta()));
+                            ui.end_row();
+                        }
+                    });
+                });
+        });
+    }
+
+    fn render_users(&mut self, ui: &mut egui::Ui) {
+        let summaries = users::summarize(&self.processes);
+        let total_memory_mb = self.metrics.get_total_memory_gb() * 1024.0;
+
+        ui.label(format!("{} users, {} processes", summaries.len(), self.processes.len()));
+        egui::ScrollArea::vertical().id_salt("user_summary").show(ui, |ui| {
+            for summary in &summaries {
+                let mine = summary.uid.is_some() && summary.uid == self.current_uid;
+                let header = format!(
+                    "{}{} — {} processes, {:.1}% CPU, {:.1} MB ({:.1}% of RAM)",
+                    summary.user,
+                    if mine { " (you)" } else { "" },
+                    summary.process_count,
+                    summary.cpu_usage,
+                    summary.memory_mb,
+                    summary.memory_mb / total_memory_mb.max(1.0) * 100.0
+                );
+                egui::CollapsingHeader::new(header)
+                    .id_salt(("user", summary.uid))
+                    .show(ui, |ui| {
+                        egui::Grid::new(("sessions", summary.uid)).striped(true).num_columns(4).show(ui, |ui| {
+                            for header in ["Session", "Processes", "CPU", "Memory"] {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+                            for session in &summary.sessions {
+                                ui.label(
+                                    session
+                                        .session_id
+                                        .map(|sid| sid.to_string())
+                                        .unwrap_or_else(|| "-".to_string()),
+                                );
+                                ui.label(session.process_count.to_string());
+                                ui.label(format!("{:.1}%", session.cpu_usage));
+                                ui.label(format!("{:.1} MB", session.memory_mb));
+                                ui.end_row();
+                            }
+                        });
+                    });
+            }
+        });
+    }
+
+    fn render_settings_warnings(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 180, 0),
+                "⚠ Problems loading settings.json; the original was kept as settings.json.bak",
+            );
+            if ui.button("Dismiss").clicked() {
+                self.settings_warnings.clear();
+            }
+        });
+        for warning in &self.settings_warnings {
+            ui.label(format!("• {}", warning));
+        }
+    }
+
+    fn render_status_footer(&mut self, ui: &mut egui::Ui) {
+        let Some(latest) = self.self_monitor.latest().copied() else {
+            ui.label("memwatch: waiting for first sample…");
+            return;
+        };
+
+        let response = egui::CollapsingHeader::new(format!(
+            "memwatch: {:.1} MB RSS · {:.1}% CPU · refresh {:.1} ms ({} processes) every {} ms ({}) · frame {:.1} ms (max {:.1} ms)",
+            latest.rss_mb,
+            latest.cpu_usage,
+            latest.refresh_ms,
+            latest.processes_scanned,
+            self.refresh_interval.0.as_millis(),
+            self.refresh_interval.1.label(),
+            latest.frame_ms,
+            latest.frame_max_ms
+        ))
+        .id_salt("status_footer_history")
+        .show(ui, |ui| {
+            let history = self.self_monitor.history();
+            let line = |value: fn(&SelfSample) -> f64| -> PlotPoints {
+                history.iter().map(|s| [s.elapsed, value(s)]).collect()
+            };
+            ui.columns(2, |columns| {
+                Plot::new("self_memory_chart")
+                    .height(90.0)
+                    .legend(egui_plot::Legend::default())
+                    .show(&mut columns[0], |plot_ui| {
+                        plot_ui.line(Line::new(line(|s| s.rss_mb)).name("RSS (MB)"));
+                        plot_ui.line(Line::new(line(|s| s.cpu_usage as f64)).name("CPU %"));
+                    });
+                Plot::new("self_timing_chart")
+                    .height(90.0)
+                    .legend(egui_plot::Legend::default())
+                    .show(&mut columns[1], |plot_ui| {
+                        plot_ui.line(Line::new(line(|s| s.refresh_ms)).name("Refresh (ms)"));
+                        plot_ui.line(Line::new(line(|s| s.frame_ms)).name("Frame (ms)"));
+                        plot_ui.line(Line::new(line(|s| s.frame_max_ms)).name("Worst frame (ms)"));
+                    });
+            });
+        });
+        response.header_response.on_hover_text("memwatch's own footprint; expand for history");
+    }
+
+    fn render_recently_exited(&mut self, ctx: &egui::Context) {
+        if !self.show_exited {
+            return;
+        }
+
+        let tracker = self.metrics.process_tracker();
+        egui::Window::new("Recently Exited")
+            .open(&mut self.show_exited)
+            .default_size([520.0, 360.0])
+            .show(ctx, |ui| {
+                ui.label(format!("Source: {}", tracker.source().label()));
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("exited_grid").striped(true).num_columns(5).show(ui, |ui| {
+                        for header in ["Exited", "PID", "Name", "Lifetime", "Peak Memory"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+
+                        for exited in tracker.recently_exited() {
+                            ui.label(format!("{:.0}s ago", exited.exited_at.elapsed().as_secs_f64()));
+                            ui.label(exited.pid.to_string());
+                            ui.label(&exited.name);
+                            ui.label(format_duration(exited.lifetime));
+                            ui.label(format!("{:.1} MB", exited.peak_memory_mb));
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+    }
+
+    fn render_history(&mut self, ctx: &egui::Context) {
+        if !self.show_history {
+            return;
+        }
+
+        let mut reload = false;
+        egui::Window::new("Kill History")
+            .open(&mut self.show_history)
+            .default_size([700.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} entries", self.history.len()));
+                    if ui.button("Reload").clicked() {
+                        reload = true;
                     }
                 });
-
                 ui.separator();
 
-                ui.heading("Hotkey");
-                if ui.checkbox(&mut self.settings.hotkey_enabled, "Enable global hotkey (⌥⌘M)").changed() {
-                    let _ = self.settings.save();
-                }
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("history_grid").striped(true).num_columns(8).show(ui, |ui| {
+                        for header in ["Time", "PID", "Name", "User", "Signal", "Result", "Origin", "Command"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
 
-                if self.hotkey_manager.is_some() {
-                    ui.label("Press ⌥⌘M anywhere to toggle window");
-                } else {
-                    ui.label("⚠ Global hotkey not available on this platform");
-                }
+                        for entry in self.history.iter().rev() {
+                            ui.label(audit::format_timestamp(entry.timestamp));
+                            ui.label(entry.pid.to_string());
+                            ui.label(&entry.name);
+                            ui.label(&entry.user);
+                            ui.label(&entry.signal);
+                            ui.label(&entry.status);
+                            ui.label(entry.origin.label());
+                            ui.label(&entry.command_line);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
 
-                ui.label("You can also use 'memwatch toggle' from CLI");
+        if reload {
+            self.history = audit::load();
+        }
+    }
 
-                if ui.button("Close").clicked() {
-                    should_close = true;
+    fn run_oom_guard(&mut self) {
+        let Some(event) = self.oom_guard.evaluate(
+            &self.settings.oom_guard,
+            &self.settings.protection,
+            self.metrics.get_available_memory_mb(),
+            &self.processes,
+            Instant::now(),
+        ) else {
+            return;
+        };
+
+        let verb = match event.action {
+            GuardAction::Terminate => "terminate",
+            GuardAction::Stop => "stop",
+        };
+        match event.status {
+            None => self.show_notification(
+                format!(
+                    "OOM guard (dry run) would {} {} ({}, {:.0} MB): {}",
+                    verb, event.name, event.pid, event.memory_mb, event.trigger
+                ),
+                NotificationLevel::Info,
+            ),
+            Some(KillStatus::Success) => self.show_notification(
+                format!(
+                    "OOM guard sent {} to {} ({}, {:.0} MB): {}",
+                    event.action.signal_name(),
+                    event.name,
+                    event.pid,
+                    event.memory_mb,
+                    event.trigger
+                ),
+                NotificationLevel::Error,
+            ),
+            Some(status) => self.show_notification(
+                format!("OOM guard could not {} {} ({}): {}", verb, event.name, event.pid, status),
+                NotificationLevel::Error,
+            ),
+        }
+    }
+
+    /// Notifies once when a filesystem crosses the fill threshold, and again
+    /// only after it has dropped back below it.
+    fn check_filesystem_alerts(&mut self) {
+        let threshold = self.settings.disk_alert_percent;
+        let mut newly_full: Vec<(String, f32)> = Vec::new();
+        for filesystem in self.metrics.filesystems() {
+            let used = filesystem.used_percent();
+            if used >= threshold {
+                if self.full_filesystems.insert(filesystem.mount_point.clone()) {
+                    newly_full.push((filesystem.mount_point.clone(), used));
                 }
-            });
+            } else {
+                self.full_filesystems.remove(&filesystem.mount_point);
+            }
+        }
 
-        if should_close {
-            self.show_settings = false;
+        if !newly_full.is_empty() {
+            let mounts: Vec<String> = newly_full
+                .iter()
+                .map(|(mount_point, used)| format!("{} is {:.0}% full", mount_point, used))
+                .collect();
+            self.show_notification(format!("⚠ {}", mounts.join(", ")), NotificationLevel::Error);
+        }
+    }
+
+    fn handle_ipc_message(&mut self, ctx: &egui::Context, msg: &str) {
+        let mut parts = msg.split_whitespace();
+        match (parts.next(), parts.next().and_then(|pid| pid.parse::<u32>().ok())) {
+            (Some("toggle"), _) => self.run_action(ctx, Action::ToggleWindow),
+            (Some("reload-settings"), _) => self.reload_settings(),
+            (Some("profile"), _) => {
+                let name = msg.trim_start().trim_start_matches("profile").trim();
+                self.run_action(ctx, Action::SwitchProfile(name.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    fn switch_profile(&mut self, name: &str) {
+        match self.settings.switch_profile(name) {
+            Ok(()) => {
+                let _ = self.settings.save();
+                self.rebuild_visible_processes();
+                self.show_notification(format!("Switched to profile \"{}\"", name), NotificationLevel::Info);
+            }
+            Err(e) => self.show_notification(e, NotificationLevel::Error),
+        }
+    }
+
+    /// Re-reads settings.json and applies whatever differs from the running
+    /// settings. Saves made by this instance read back identical and are
+    /// ignored.
+    fn reload_settings(&mut self) {
+        let (settings, warnings) = match Settings::reload() {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.settings_warnings = vec![e];
+                return;
+            }
+        };
+        if !warnings.is_empty() {
+            self.settings_warnings = warnings;
+        }
+        if settings.same_as(&self.settings) {
+            return;
+        }
+
+        if settings.process_source != self.settings.process_source {
+            self.metrics.set_source(settings.process_source);
         }
+        self.protection_text = protection_lists_to_text(&settings.protection);
+        self.oom_allowlist_text = settings.oom_guard.allowlist.join(", ");
+        self.keymap = settings.keybindings.keymap().0;
+        self.keybinding_text = keybindings_to_text(&settings.keybindings);
+        self.settings = settings;
+        self.rebuild_visible_processes();
+        self.sync_global_hotkey();
+        self.show_notification("Settings reloaded from settings.json".to_string(), NotificationLevel::Info);
     }
 
     fn toggle_window(&mut self, ctx: &egui::Context) {
@@ -362,52 +2212,444 @@ impl MemwatchApp {
             ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
         }
     }
-}
 
-impl eframe::App for MemwatchApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Check for global hotkey: Option+Command+M
-        if self.settings.hotkey_enabled {
-            ctx.input(|i| {
-                if i.modifiers.command && i.modifiers.alt && !i.modifiers.shift && !i.modifiers.ctrl {
-                    if i.key_pressed(egui::Key::M) {
-                        self.toggle_window(ctx);
+    /// Registers the window toggle binding system-wide, or releases it when
+    /// the hotkey is disabled.
+    fn sync_global_hotkey(&mut self) {
+        let binding = self
+            .keymap
+            .iter()
+            .find(|(action, _)| *action == KeyAction::ToggleWindow)
+            .map(|(_, binding)| *binding)
+            .filter(|_| self.settings.hotkey_enabled);
+        if let Some(hotkey) = &mut self.hotkey_manager {
+            hotkey.set_binding(binding);
+        }
+    }
+
+    /// Runs the actions bound to this frame's key presses and removes those
+    /// presses from the input, so widgets do not handle them a second time.
+    fn handle_keyboard(&mut self, ctx: &egui::Context) {
+        let typing = ctx.wants_keyboard_input();
+        let focused = ctx.memory(|mem| mem.focused().is_some());
+        let modal = self.pending_batch.is_some() || self.show_signal_menu || self.palette.is_some();
+        let available: Vec<(KeyAction, KeyBinding)> = self
+            .keymap
+            .iter()
+            .copied()
+            .filter(|(action, _)| match action {
+                // A global backend already delivers the press, even to this window.
+                KeyAction::ToggleWindow => {
+                    self.settings.hotkey_enabled
+                        && self.hotkey_manager.as_ref().is_some_and(|hotkey| !hotkey.is_global())
+                }
+                // Also closes the palette again while its search box has focus.
+                KeyAction::CommandPalette => self.pending_batch.is_none() && !self.show_signal_menu,
+                // Text fields handle Esc themselves by dropping focus.
+                KeyAction::CloseDialog => !typing,
+                _ if focused || modal => false,
+                KeyAction::SortOomScore => cfg!(target_os = "linux") && self.active_view == View::Processes,
+                _ => self.active_view == View::Processes,
+            })
+            .collect();
+
+        let actions: Vec<KeyAction> = ctx.input_mut(|input| {
+            let mut actions = Vec::new();
+            input.events.retain(|event| {
+                let egui::Event::Key { key, modifiers, pressed: true, .. } = event else {
+                    return true;
+                };
+                match available.iter().find(|(_, binding)| binding.matches(*key, *modifiers)) {
+                    Some((action, _)) => {
+                        actions.push(*action);
+                        false
                     }
+                    None => true,
                 }
             });
+            actions
+        });
+
+        for action in actions {
+            self.run_action(ctx, action.into());
         }
+    }
+
+    /// Performs an action from any source: key bindings, the command
+    /// palette, the global hotkey or IPC.
+    fn run_action(&mut self, ctx: &egui::Context, action: Action) {
+        let cursor = self
+            .selection_anchor
+            .and_then(|pid| self.visible_processes.iter().position(|p| p.pid == pid));
+        let last = self.visible_processes.len().saturating_sub(1);
+        let page = self.page_rows.max(1);
 
-        if let Some(ref ipc) = self.ipc_server {
-            if let Some(msg) = ipc.check_message() {
-                if msg == "toggle" {
-                    self.toggle_window(ctx);
+        match action {
+            Action::ToggleWindow => self.toggle_window(ctx),
+            Action::CommandPalette => {
+                self.palette = match self.palette {
+                    Some(_) => None,
+                    None => Some(CommandPalette::default()),
+                };
+            }
+            Action::FocusFilter => {
+                self.active_view = View::Processes;
+                self.focus_filter = true;
+            }
+            Action::SelectPrevious => self.move_cursor(cursor.map_or(last, |i| i.saturating_sub(1))),
+            Action::SelectNext => self.move_cursor(cursor.map_or(0, |i| (i + 1).min(last))),
+            Action::PageUp => self.move_cursor(cursor.map_or(0, |i| i.saturating_sub(page))),
+            Action::PageDown => self.move_cursor(cursor.map_or(0, |i| (i + page).min(last))),
+            Action::SelectFirst => self.move_cursor(0),
+            Action::SelectLast => self.move_cursor(last),
+            Action::Sort(mode) => self.set_sort_mode(mode),
+            Action::Terminate => {
+                if self.selected.len() == 1 {
+                    let pid = *self.selected.iter().next().unwrap();
+                    self.terminate(pid, KillOrigin::Gui);
+                } else if !self.selected.is_empty() {
+                    self.pending_batch = Some(BatchAction::Terminate);
+                    self.focus_dialog = true;
+                }
+            }
+            Action::ForceKill => {
+                if !self.selected.is_empty() {
+                    self.pending_batch = Some(BatchAction::ForceKill);
+                    self.focus_dialog = true;
+                }
+            }
+            Action::SignalMenu => {
+                if !self.selected.is_empty() {
+                    self.show_signal_menu = true;
+                    self.focus_dialog = true;
+                }
+            }
+            Action::ToggleTree => {
+                self.settings.tree_view = !self.settings.tree_view;
+                let _ = self.settings.save();
+                self.rebuild_visible_processes();
+                if let Some(index) = self
+                    .selection_anchor
+                    .and_then(|pid| self.visible_processes.iter().position(|p| p.pid == pid))
+                {
+                    self.scroll_to_row = Some(index);
+                }
+            }
+            Action::OpenDetails => {
+                if let Some(index) = cursor {
+                    self.open_details(self.visible_processes[index].pid);
+                }
+            }
+            Action::CloseDialog => self.close_top_dialog(),
+            Action::ToggleChartSeries(line) => {
+                let shown = self.settings.chart_series.get_mut(line);
+                *shown = !*shown;
+                let state = if *shown { "shown" } else { "hidden" };
+                let _ = self.settings.save();
+                self.show_notification(format!("Chart: {} {}", line.label(), state), NotificationLevel::Info);
+            }
+            Action::OpenSettings => self.show_settings = true,
+            Action::OpenHistory => {
+                self.show_history = true;
+                self.history = audit::load();
+            }
+            Action::OpenExited => self.show_exited = true,
+            Action::SwitchProfile(name) => self.switch_profile(&name),
+            Action::PinSnapshot => {
+                let snapshot = Snapshot::new(&self.processes);
+                self.snapshot_diff = Some(snapshot.diff(&self.processes));
+                self.pinned_snapshot = Some(snapshot);
+                self.active_view = View::Changes;
+            }
+            Action::ExportSnapshot => {
+                // The pinned snapshot if there is one, otherwise the live list.
+                let live;
+                let snapshot = match &self.pinned_snapshot {
+                    Some(snapshot) => snapshot,
+                    None => {
+                        live = Snapshot::new(&self.processes);
+                        &live
+                    }
+                };
+                match snapshot.export() {
+                    Ok(path) => self.show_notification(
+                        format!("Snapshot exported to {}", path.display()),
+                        NotificationLevel::Success,
+                    ),
+                    Err(e) => self.show_notification(format!("Export failed: {}", e), NotificationLevel::Error),
                 }
             }
         }
+    }
+
+    /// Shows `pid` in the process list and selects it, clearing the filter
+    /// or "Only mine" if either hides it.
+    fn jump_to_process(&mut self, pid: u32) {
+        self.active_view = View::Processes;
+        let position = |app: &Self| app.visible_processes.iter().position(|p| p.pid == pid);
+        let mut changed = false;
+        if position(self).is_none() && !self.settings.process_filter.is_empty() {
+            self.settings.process_filter.clear();
+            self.rebuild_visible_processes();
+            changed = true;
+        }
+        if position(self).is_none() && self.settings.only_current_user {
+            self.settings.only_current_user = false;
+            self.rebuild_visible_processes();
+            changed = true;
+        }
+        if changed {
+            let _ = self.settings.save();
+        }
+        match position(self) {
+            Some(index) => self.move_cursor(index),
+            None => self.show_notification(format!("Process {} has exited", pid), NotificationLevel::Error),
+        }
+    }
+
+    /// Ctrl+K / ⌘K overlay searching the action registry and processes.
+    /// Arrow keys move, Enter runs the highlighted item, Esc closes.
+    fn render_command_palette(&mut self, ctx: &egui::Context) {
+        let Some(palette) = &mut self.palette else {
+            return;
+        };
+
+        // Taken before the search box sees them, so Enter and Esc do not
+        // just drop its focus.
+        let (up, down, enter, escape) = ctx.input_mut(|input| {
+            (
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+
+        let actions = actions::palette_actions(&self.settings);
+        let items = palette::search(&palette.query, &actions, &self.processes);
+        if down {
+            palette.selected += 1;
+        }
+        if up {
+            palette.selected = palette.selected.saturating_sub(1);
+        }
+        palette.selected = palette.selected.min(items.len().saturating_sub(1));
+
+        let keymap = &self.keymap;
+        let shortcut = |action: &Action| {
+            let key_action = action.key_action()?;
+            keymap
+                .iter()
+                .find(|(bound, _)| *bound == key_action)
+                .map(|(_, binding)| binding.to_string())
+        };
+
+        let mut chosen = enter.then(|| items.get(palette.selected).cloned()).flatten();
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size([460.0, 0.0])
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .show(ctx, |ui| {
+                let search = ui.add(
+                    egui::TextEdit::singleline(&mut palette.query)
+                        .hint_text("Type an action, process name or PID")
+                        .desired_width(f32::INFINITY),
+                );
+                search.request_focus();
+                if search.changed() {
+                    palette.selected = 0;
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    if items.is_empty() {
+                        ui.weak("No matches");
+                    }
+                    for (i, item) in items.iter().enumerate() {
+                        let is_selected = i == palette.selected;
+                        let row = ui.horizontal(|ui| {
+                            let (label, hint) = match item {
+                                PaletteItem::Action(action) => (action.label(), shortcut(action).unwrap_or_default()),
+                                PaletteItem::Process { label, .. } => (label.clone(), "process".to_string()),
+                            };
+                            let response = ui.selectable_label(is_selected, label);
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.weak(hint);
+                            });
+                            response
+                        });
+                        if is_selected && (up || down) {
+                            row.inner.scroll_to_me(None);
+                        }
+                        if row.inner.clicked() {
+                            chosen = Some(item.clone());
+                        }
+                    }
+                });
+
+                ui.separator();
+                ui.weak("↑↓ to move, Enter to run, Esc to close. A chosen process is selected for the process shortcuts.");
+            });
+
+        if chosen.is_none() && !escape {
+            return;
+        }
+        self.palette = None;
+        ctx.memory_mut(|mem| mem.stop_text_input());
+        match chosen {
+            Some(PaletteItem::Action(action)) => self.run_action(ctx, action),
+            Some(PaletteItem::Process { pid, .. }) => self.jump_to_process(pid),
+            None => {}
+        }
+    }
+
+    /// Selects only the process at `index` and scrolls it into view.
+    fn move_cursor(&mut self, index: usize) {
+        let Some(process) = self.visible_processes.get(index) else {
+            return;
+        };
+        self.selected.clear();
+        self.selected.insert(process.pid);
+        self.selection_anchor = Some(process.pid);
+        self.scroll_to_row = Some(index);
+    }
+
+    fn close_top_dialog(&mut self) {
+        if self.palette.is_some() {
+            self.palette = None;
+        } else if self.show_signal_menu {
+            self.show_signal_menu = false;
+        } else if self.pending_batch.is_some() {
+            self.pending_batch = None;
+        } else if self.kill_confirmation.is_some() {
+            self.kill_confirmation = None;
+        } else if self.details.is_some() {
+            self.details = None;
+        } else if self.show_history {
+            self.show_history = false;
+        } else if self.show_exited {
+            self.show_exited = false;
+        } else if self.show_settings {
+            self.show_settings = false;
+        }
+    }
+}
+
+impl eframe::App for MemwatchApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let frame_started = Instant::now();
+
+        self.handle_keyboard(ctx);
+
+        if self
+            .hotkey_manager
+            .as_ref()
+            .is_some_and(|hotkey| hotkey.check_triggered())
+        {
+            self.run_action(ctx, Action::ToggleWindow);
+        }
 
-        if self.last_refresh.elapsed() >= Duration::from_millis(self.settings.refresh_interval_ms) {
+        while let Ok(outcome) = self.kill_results.try_recv() {
+            self.handle_kill_outcome(outcome);
+        }
+
+        if let Some(msg) = self.ipc_server.as_ref().and_then(|ipc| ipc.check_message()) {
+            self.handle_ipc_message(ctx, &msg);
+        }
+
+        if self.settings_watcher.poll() {
+            self.reload_settings();
+        }
+
+        self.refresh_interval = self.scheduler.next_interval(
+            &self.settings.refresh_policy,
+            self.settings.refresh_interval_ms,
+            self.window_visible,
+            Instant::now(),
+        );
+        if self.last_refresh.elapsed() >= self.refresh_interval.0 {
             self.metrics.refresh();
+            if let (Some(&(_, cpu)), Some(&(_, memory_gb))) = (
+                self.metrics.get_cpu_history().last(),
+                self.metrics.get_memory_history().last(),
+            ) {
+                self.scheduler
+                    .observe(&self.settings.refresh_policy, Instant::now(), cpu, memory_gb * 1024.0);
+            }
             self.processes = self.metrics.get_processes();
+            self.self_monitor.record_refresh(
+                &self.processes,
+                self.metrics.refresh_duration(),
+                self.metrics.sampling_cost().processes_scanned,
+            );
+            let live: HashSet<u32> = self.processes.iter().map(|p| p.pid).collect();
+            self.selected.retain(|pid| live.contains(pid));
+            self.rebuild_visible_processes();
+            self.snapshot_diff = self.pinned_snapshot.as_ref().map(|s| s.diff(&self.processes));
             self.last_refresh = Instant::now();
+            self.run_oom_guard();
+            self.check_filesystem_alerts();
         }
 
         self.render_notification(ctx);
 
+        if !self.settings_warnings.is_empty() {
+            egui::TopBottomPanel::top("settings_warnings").show(ctx, |ui| {
+                self.render_settings_warnings(ui);
+            });
+        }
+
+        if self.settings.show_status_footer {
+            egui::TopBottomPanel::bottom("status_footer").show(ctx, |ui| {
+                self.render_status_footer(ui);
+            });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            self.render_toolbar(ui);
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.active_view, View::Processes, "Processes");
+                ui.selectable_value(&mut self.active_view, View::Changes, "Changes");
+                ui.selectable_value(&mut self.active_view, View::Users, "Users");
+
+                // Window buttons stay reachable from every view.
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("⚙ Settings").clicked() {
+                        self.show_settings = !self.show_settings;
+                    }
+                    if ui.button("Exited").on_hover_text("Recently exited processes").clicked() {
+                        self.show_exited = !self.show_exited;
+                    }
+                    if ui.button("History").clicked() {
+                        self.show_history = !self.show_history;
+                        if self.show_history {
+                            self.history = audit::load();
+                        }
+                    }
+                });
+            });
+
+            if self.active_view == View::Processes {
+                self.render_toolbar(ui);
+            }
 
             ui.separator();
 
             ui.allocate_ui_with_layout(
                 egui::vec2(ui.available_width(), ui.available_height() * 0.65),
                 egui::Layout::top_down(egui::Align::Min),
-                |ui| {
-                    self.render_process_list(ui);
+                |ui| match self.active_view {
+                    View::Processes => self.render_process_list(ui),
+                    View::Changes => self.render_changes(ui),
+                    View::Users => self.render_users(ui),
                 },
             );
 
             ui.separator();
 
+            self.render_sensors(ui);
+            self.render_filesystems(ui);
             self.render_chart(ui);
         });
 
@@ -415,6 +2657,199 @@ impl eframe::App for MemwatchApp {
             self.render_settings(ctx);
         }
 
-        ctx.request_repaint_after(Duration::from_millis(self.settings.refresh_interval_ms));
+        self.render_signal_menu(ctx);
+        self.render_batch_confirmation(ctx);
+        self.render_history(ctx);
+        self.render_details(ctx);
+        self.render_recently_exited(ctx);
+        self.render_command_palette(ctx);
+
+        self.self_monitor.record_frame(frame_started.elapsed());
+        ctx.request_repaint_after(self.refresh_interval.0.saturating_sub(self.last_refresh.elapsed()));
+    }
+}
+
+/// Formats a process lifetime compactly: milliseconds for short-lived
+/// processes, seconds, minutes or hours otherwise.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    if secs < 1.0 {
+        format!("{} ms", duration.as_millis())
+    } else if secs < 60.0 {
+        format!("{:.1} s", secs)
+    } else if secs < 3600.0 {
+        format!("{:.0} min", secs / 60.0)
+    } else {
+        format!("{:.1} h", secs / 3600.0)
+    }
+}
+
+/// Formats a bytes-per-second rate with a unit suited to its magnitude.
+fn format_rate(bytes_per_sec: f64) -> String {
+    if bytes_per_sec >= 1_048_576.0 {
+        format!("{:.1} MB/s", bytes_per_sec / 1_048_576.0)
+    } else if bytes_per_sec >= 1024.0 {
+        format!("{:.0} KB/s", bytes_per_sec / 1024.0)
+    } else if bytes_per_sec > 0.0 {
+        format!("{:.0} B/s", bytes_per_sec)
+    } else {
+        "0".to_string()
+    }
+}
+
+/// Edits one escalation policy in place and reports whether anything changed.
+fn escalation_policy_editor(ui: &mut egui::Ui, policy: &mut EscalationPolicy, named: bool) -> bool {
+    let mut changed = false;
+
+    if named {
+        ui.horizontal(|ui| {
+            ui.label("Process name:");
+            changed |= ui.text_edit_singleline(&mut policy.process_name).changed();
+        });
+    }
+
+    let mut remove: Option<usize> = None;
+    for (index, step) in policy.steps.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}.", index + 1));
+            egui::ComboBox::from_id_salt(ui.id().with(("step_signal", index)))
+                .selected_text(step.signal.name())
+                .show_ui(ui, |ui| {
+                    for signal in GracefulSignal::ALL {
+                        changed |= ui.selectable_value(&mut step.signal, signal, signal.name()).changed();
+                    }
+                });
+            ui.label("then wait");
+            changed |= ui
+                .add(egui::DragValue::new(&mut step.wait_ms).range(0..=120_000).suffix(" ms"))
+                .changed();
+            if ui.small_button("✖").clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        policy.steps.remove(index);
+        changed = true;
+    }
+    if ui.small_button("Add step").clicked() {
+        policy.steps.push(EscalationStep {
+            signal: GracefulSignal::Term,
+            wait_ms: 1500,
+        });
+        changed = true;
+    }
+
+    changed |= ui
+        .checkbox(&mut policy.confirm_sigkill, "Ask before escalating to SIGKILL")
+        .changed();
+
+    changed
+}
+
+fn keybindings_to_text(bindings: &KeyBindings) -> Vec<String> {
+    KeyAction::ALL.iter().map(|&action| bindings.get(action).clone()).collect()
+}
+
+/// Orders processes depth-first under their parents, keeping the current sort
+/// among siblings. A process whose parent is not in the list is a root, and
+/// so is the first member of a parent cycle, which a PPID read racing a
+/// reparent can produce.
+fn tree_order(processes: Vec<ProcessInfo>) -> (Vec<ProcessInfo>, Vec<usize>) {
+    let index_of: HashMap<u32, usize> = processes.iter().enumerate().map(|(i, p)| (p.pid, i)).collect();
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (i, process) in processes.iter().enumerate() {
+        match process.parent.and_then(|parent| index_of.get(&parent)) {
+            Some(&parent) if parent != i => children.entry(parent).or_default().push(i),
+            _ => roots.push(i),
+        }
+    }
+
+    let mut order = Vec::with_capacity(processes.len());
+    let mut depths = Vec::with_capacity(processes.len());
+    let mut visited = vec![false; processes.len()];
+    let mut stack = Vec::new();
+    for root in roots.into_iter().chain(0..processes.len()) {
+        if visited[root] {
+            continue;
+        }
+        stack.push((root, 0));
+        while let Some((i, depth)) = stack.pop() {
+            if std::mem::replace(&mut visited[i], true) {
+                continue;
+            }
+            order.push(i);
+            depths.push(depth);
+            if let Some(kids) = children.get(&i) {
+                stack.extend(kids.iter().rev().map(|&kid| (kid, depth + 1)));
+            }
+        }
+    }
+
+    let mut slots: Vec<Option<ProcessInfo>> = processes.into_iter().map(Some).collect();
+    let ordered = order.into_iter().filter_map(|i| slots[i].take()).collect();
+    (ordered, depths)
+}
+
+fn protection_lists_to_text(rules: &ProtectionRules) -> [String; 4] {
+    [
+        rules.names.join("\n"),
+        rules.paths.join("\n"),
+        rules.pids.iter().map(|pid| pid.to_string()).collect::<Vec<_>>().join("\n"),
+        rules.uids.iter().map(|uid| uid.to_string()).collect::<Vec<_>>().join("\n"),
+    ]
+}
+
+fn protection_lists_from_text(text: &[String; 4]) -> ProtectionRules {
+    let entries = |s: &str| -> Vec<String> {
+        s.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    ProtectionRules {
+        names: entries(&text[0]),
+        paths: entries(&text[1]),
+        pids: entries(&text[2]).iter().filter_map(|e| e.parse().ok()).collect(),
+        uids: entries(&text[3]).iter().filter_map(|e| e.parse().ok()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, parent: Option<u32>) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: String::new(),
+            parent,
+            cpu_usage: 0.0,
+            memory_mb: 0.0,
+            disk_read_bps: 0.0,
+            disk_write_bps: 0.0,
+            exe: None,
+            uid: None,
+            user: None,
+            session_id: None,
+            oom_score: None,
+            oom_score_adj: None,
+        }
+    }
+
+    #[test]
+    fn tree_order_keeps_parent_cycles() {
+        let (ordered, depths) = tree_order(vec![
+            process(1, None),
+            process(20, Some(21)),
+            process(2, Some(1)),
+            process(21, Some(20)),
+            process(22, Some(21)),
+        ]);
+        let pids: Vec<u32> = ordered.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, [1, 2, 20, 21, 22]);
+        assert_eq!(depths, [0, 1, 0, 1, 2]);
     }
 }
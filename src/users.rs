@@ -0,0 +1,148 @@
+// Per-user and per-session aggregation of the process list.
+
+use crate::metrics::ProcessInfo;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct SessionSummary {
+    pub session_id: Option<u32>,
+    pub process_count: usize,
+    pub cpu_usage: f32,
+    pub memory_mb: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UserSummary {
+    pub uid: Option<u32>,
+    /// Account name, or the numeric UID when it has no passwd entry.
+    pub user: String,
+    pub process_count: usize,
+    pub cpu_usage: f32,
+    pub memory_mb: f64,
+    /// Largest memory consumer first.
+    pub sessions: Vec<SessionSummary>,
+}
+
+type SessionMap = BTreeMap<Option<u32>, SessionSummary>;
+
+/// UID memwatch itself runs as; `None` off Unix.
+#[cfg(unix)]
+pub fn current_uid() -> Option<u32> {
+    Some(unsafe { libc::getuid() })
+}
+
+#[cfg(not(unix))]
+pub fn current_uid() -> Option<u32> {
+    None
+}
+
+/// Groups processes by owner, heaviest memory user first.
+pub fn summarize(processes: &[ProcessInfo]) -> Vec<UserSummary> {
+    let mut users: BTreeMap<Option<u32>, (UserSummary, SessionMap)> = BTreeMap::new();
+
+    for process in processes {
+        let (user, sessions) = users.entry(process.uid).or_insert_with(|| {
+            let summary = UserSummary {
+                uid: process.uid,
+                user: display_name(process),
+                ..Default::default()
+            };
+            (summary, BTreeMap::new())
+        });
+        user.process_count += 1;
+        user.cpu_usage += process.cpu_usage;
+        user.memory_mb += process.memory_mb;
+
+        let session = sessions.entry(process.session_id).or_insert_with(|| SessionSummary {
+            session_id: process.session_id,
+            ..Default::default()
+        });
+        session.process_count += 1;
+        session.cpu_usage += process.cpu_usage;
+        session.memory_mb += process.memory_mb;
+    }
+
+    let mut summaries: Vec<UserSummary> = users
+        .into_values()
+        .map(|(mut user, sessions)| {
+            user.sessions = sessions.into_values().collect();
+            user.sessions.sort_by(|a, b| {
+                b.memory_mb
+                    .partial_cmp(&a.memory_mb)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            user
+        })
+        .collect();
+    summaries.sort_by(|a, b| {
+        b.memory_mb
+            .partial_cmp(&a.memory_mb)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    summaries
+}
+
+fn display_name(process: &ProcessInfo) -> String {
+    match (&process.user, process.uid) {
+        (Some(name), _) => name.clone(),
+        (None, Some(uid)) => uid.to_string(),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(uid: Option<u32>, user: Option<&str>, session_id: Option<u32>, memory_mb: f64) -> ProcessInfo {
+        ProcessInfo {
+            pid: 0,
+            name: String::new(),
+            parent: None,
+            cpu_usage: 1.0,
+            memory_mb,
+            disk_read_bps: 0.0,
+            disk_write_bps: 0.0,
+            exe: None,
+            uid,
+            user: user.map(str::to_string),
+            session_id,
+            oom_score: None,
+            oom_score_adj: None,
+        }
+    }
+
+    #[test]
+    fn groups_by_user_then_session() {
+        let summaries = summarize(&[
+            process(Some(1000), Some("alice"), Some(5), 100.0),
+            process(Some(1000), Some("alice"), Some(5), 50.0),
+            process(Some(1000), Some("alice"), Some(9), 400.0),
+            process(Some(1000), Some("alice"), None, 10.0),
+            process(Some(2000), None, Some(7), 1000.0),
+            process(None, None, None, 5.0),
+        ]);
+
+        let users: Vec<(Option<u32>, &str, usize, f64)> = summaries
+            .iter()
+            .map(|u| (u.uid, u.user.as_str(), u.process_count, u.memory_mb))
+            .collect();
+        assert_eq!(
+            users,
+            [
+                (Some(2000), "2000", 1, 1000.0),
+                (Some(1000), "alice", 4, 560.0),
+                (None, "unknown", 1, 5.0),
+            ]
+        );
+
+        let alice = &summaries[1];
+        assert_eq!(alice.cpu_usage, 4.0);
+        let sessions: Vec<(Option<u32>, usize, f64)> = alice
+            .sessions
+            .iter()
+            .map(|s| (s.session_id, s.process_count, s.memory_mb))
+            .collect();
+        assert_eq!(sessions, [(Some(9), 1, 400.0), (Some(5), 2, 150.0), (None, 1, 10.0)]);
+    }
+}
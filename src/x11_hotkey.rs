@@ -0,0 +1,236 @@
+// System-wide hotkey under X11: grabs the key combination on the root window
+// from a private connection and waits for presses on a background thread.
+
+use crate::hotkey::HotkeyTrigger;
+use crate::keybindings::KeyBinding;
+use eframe::egui::Key;
+use std::os::unix::io::AsRawFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt as _, GrabMode, Keycode, ModMask, Window};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+pub struct X11Hotkey {
+    binding: KeyBinding,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl X11Hotkey {
+    /// Grabs `binding` on the default display. Fails if the key has no
+    /// keycode in the current layout or another client already grabbed it.
+    pub fn register(binding: KeyBinding, trigger: HotkeyTrigger) -> Result<Self, String> {
+        let (conn, screen) = RustConnection::connect(None).map_err(|e| format!("cannot open X display: {}", e))?;
+        let root = conn.setup().roots[screen].root;
+
+        let (keysym, _) = keysym(binding.key).ok_or_else(|| format!("{} has no X11 keysym", binding))?;
+        let keycode = keycode_for(&conn, keysym)?.ok_or_else(|| format!("{} is not on this keyboard layout", binding))?;
+
+        let mut modifiers = ModMask::from(0u16);
+        if binding.modifiers.ctrl || binding.modifiers.command {
+            modifiers |= ModMask::CONTROL;
+        }
+        if binding.modifiers.alt {
+            modifiers |= ModMask::M1;
+        }
+        if binding.modifiers.shift {
+            modifiers |= ModMask::SHIFT;
+        }
+
+        // Grab once per Caps Lock / Num Lock state, as X treats those as
+        // modifiers too.
+        let variants = lock_variants(modifiers);
+        for &mask in &variants {
+            conn.grab_key(false, root, mask, keycode, GrabMode::ASYNC, GrabMode::ASYNC)
+                .map_err(|e| e.to_string())?
+                .check()
+                .map_err(|_| format!("{} is already taken by another application", binding))?;
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            thread::spawn(move || listen(conn, root, keycode, &variants, &stop, &trigger))
+        };
+        Ok(Self {
+            binding,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn binding(&self) -> KeyBinding {
+        self.binding
+    }
+}
+
+impl Drop for X11Hotkey {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn listen(
+    conn: RustConnection,
+    root: Window,
+    keycode: Keycode,
+    variants: &[ModMask],
+    stop: &AtomicBool,
+    trigger: &HotkeyTrigger,
+) {
+    let fd = conn.stream().as_raw_fd();
+    let mut last_press: Option<Instant> = None;
+
+    while !stop.load(Ordering::Relaxed) {
+        loop {
+            match conn.poll_for_event() {
+                Ok(Some(Event::KeyPress(event))) if event.detail == keycode => {
+                    // Holding the keys auto-repeats; count that as one press.
+                    if last_press.is_none_or(|at| at.elapsed() > Duration::from_millis(300)) {
+                        trigger.fire();
+                    }
+                    last_press = Some(Instant::now());
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => break,
+                Err(_) => return,
+            }
+        }
+        // Wake up periodically so dropping the hotkey is noticed.
+        let mut pollfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        unsafe {
+            libc::poll(&mut pollfd, 1, 200);
+        }
+    }
+
+    for &mask in variants {
+        let _ = conn.ungrab_key(keycode, root, mask);
+    }
+    let _ = conn.flush();
+}
+
+fn lock_variants(modifiers: ModMask) -> Vec<ModMask> {
+    [ModMask::from(0u16), ModMask::LOCK, ModMask::M2, ModMask::LOCK | ModMask::M2]
+        .into_iter()
+        .map(|lock| modifiers | lock)
+        .collect()
+}
+
+fn keycode_for(conn: &RustConnection, keysym: u32) -> Result<Option<Keycode>, String> {
+    let setup = conn.setup();
+    let (min, max) = (setup.min_keycode, setup.max_keycode);
+    let mapping = conn
+        .get_keyboard_mapping(min, max - min + 1)
+        .map_err(|e| e.to_string())?
+        .reply()
+        .map_err(|e| e.to_string())?;
+    let per_keycode = mapping.keysyms_per_keycode.max(1) as usize;
+    Ok(mapping
+        .keysyms
+        .chunks(per_keycode)
+        .position(|syms| syms.contains(&keysym))
+        .map(|i| min + i as u8))
+}
+
+/// X keysym value and xkb name for a key, e.g. `(0x6b, "k")` for K.
+pub fn keysym(key: Key) -> Option<(u32, String)> {
+    let name = key.name();
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphanumeric() {
+            let c = c.to_ascii_lowercase();
+            return Some((c as u32, c.to_string()));
+        }
+    }
+    if let Some(n) = name.strip_prefix('F').and_then(|n| n.parse::<u32>().ok()) {
+        return (1..=35).contains(&n).then(|| (0xffbe + n - 1, name.to_string()));
+    }
+
+    let (keysym, name) = match key {
+        Key::Escape => (0xff1b, "Escape"),
+        Key::Tab => (0xff09, "Tab"),
+        Key::Backspace => (0xff08, "BackSpace"),
+        Key::Enter => (0xff0d, "Return"),
+        Key::Space => (0x20, "space"),
+        Key::Insert => (0xff63, "Insert"),
+        Key::Delete => (0xffff, "Delete"),
+        Key::Home => (0xff50, "Home"),
+        Key::End => (0xff57, "End"),
+        Key::PageUp => (0xff55, "Prior"),
+        Key::PageDown => (0xff56, "Next"),
+        Key::ArrowLeft => (0xff51, "Left"),
+        Key::ArrowUp => (0xff52, "Up"),
+        Key::ArrowRight => (0xff53, "Right"),
+        Key::ArrowDown => (0xff54, "Down"),
+        Key::Minus => (0x2d, "minus"),
+        Key::Equals => (0x3d, "equal"),
+        Key::Comma => (0x2c, "comma"),
+        Key::Period => (0x2e, "period"),
+        Key::Slash => (0x2f, "slash"),
+        Key::Backslash => (0x5c, "backslash"),
+        Key::Semicolon => (0x3b, "semicolon"),
+        Key::Quote => (0x27, "apostrophe"),
+        Key::Backtick => (0x60, "grave"),
+        Key::OpenBracket => (0x5b, "bracketleft"),
+        Key::CloseBracket => (0x5d, "bracketright"),
+        _ => return None,
+    };
+    Some((keysym, name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui;
+    use x11rb::protocol::xproto::{KEY_PRESS_EVENT, KEY_RELEASE_EVENT};
+    use x11rb::protocol::xtest::ConnectionExt as _;
+
+    #[test]
+    #[ignore = "needs an X server with XTEST: xvfb-run cargo test x11 -- --ignored"]
+    fn grabs_and_fires_on_a_synthetic_press() {
+        let trigger = HotkeyTrigger::new(&egui::Context::default());
+        let hotkey = X11Hotkey::register(KeyBinding::parse("Ctrl+Alt+M").unwrap(), trigger.clone()).unwrap();
+
+        // A second client types the combination through XTEST.
+        let (conn, screen) = RustConnection::connect(None).unwrap();
+        let root = conn.setup().roots[screen].root;
+        let keys: Vec<Keycode> = [0xffe3, 0xffe9, keysym(Key::M).unwrap().0]
+            .into_iter()
+            .map(|keysym| keycode_for(&conn, keysym).unwrap().unwrap())
+            .collect();
+        for &key in &keys {
+            conn.xtest_fake_input(KEY_PRESS_EVENT, key, 0, root, 0, 0, 0).unwrap();
+        }
+        for &key in keys.iter().rev() {
+            conn.xtest_fake_input(KEY_RELEASE_EVENT, key, 0, root, 0, 0, 0).unwrap();
+        }
+        conn.flush().unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !trigger.take() {
+            assert!(Instant::now() < deadline, "the grabbed key never fired");
+            thread::sleep(Duration::from_millis(20));
+        }
+        drop(hotkey);
+    }
+
+    #[test]
+    fn maps_keys_to_keysyms() {
+        assert_eq!(keysym(Key::M), Some((0x6d, "m".to_string())));
+        assert_eq!(keysym(Key::Num7), Some((0x37, "7".to_string())));
+        assert_eq!(keysym(Key::F12), Some((0xffc9, "F12".to_string())));
+        assert_eq!(keysym(Key::Slash), Some((0x2f, "slash".to_string())));
+        assert_eq!(keysym(Key::Copy), None);
+    }
+}